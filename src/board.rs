@@ -0,0 +1,240 @@
+//! Named board pin definitions, see [`tca9534_pins!`](crate::tca9534_pins).
+
+use crate::error::Tca9534CoreError;
+use crate::initial_config::InitialConfig;
+use crate::registers::{Pin, PinLevel, PinPolarity};
+use crate::tca9534::Tca9534Sync;
+use crate::transport::SyncTransport;
+
+/// Role of a pin in a board definition.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PinDef {
+    /// Output pin with its initial level.
+    Output(Pin, PinLevel),
+    /// Input pin with its polarity.
+    Input(Pin, PinPolarity),
+}
+
+impl PinDef {
+    /// Get the pin number.
+    pub const fn pin(self) -> Pin {
+        match self {
+            PinDef::Output(pin, _) | PinDef::Input(pin, _) => pin,
+        }
+    }
+}
+
+/// Initial configuration of a board definition.
+///
+/// Panics (a compile-time error in const context) on an out-of-range or duplicate pin.
+#[doc(hidden)]
+pub const fn __initial_config(defs: &[PinDef]) -> InitialConfig {
+    let mut initial = InitialConfig::new();
+    let mut index = 0;
+    while index < defs.len() {
+        initial = match defs[index] {
+            PinDef::Output(pin, level) => initial.with_outputs(&[pin], level),
+            PinDef::Input(pin, PinPolarity::Normal) => initial.with_inputs(&[pin]),
+            PinDef::Input(pin, PinPolarity::Inverted) => {
+                initial.with_inputs(&[pin]).with_inverted(&[pin])
+            }
+        };
+        index += 1;
+    }
+    initial
+}
+
+/// Output pin of a board definition.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BoardOutputPin {
+    pin: Pin,
+}
+
+impl BoardOutputPin {
+    #[doc(hidden)]
+    pub const fn __new(pin: Pin) -> Self {
+        Self { pin }
+    }
+
+    /// Get the pin number.
+    pub const fn pin(&self) -> Pin {
+        self.pin
+    }
+
+    /// Drive the pin to a level.
+    pub fn set_level<T>(&self, tca: &mut Tca9534Sync<T>, level: PinLevel) -> Result<(), T::Error>
+    where
+        T: SyncTransport,
+        T::Error: From<Tca9534CoreError>,
+    {
+        tca.set_pin_output(self.pin, level)
+    }
+
+    /// Drive the pin high.
+    pub fn set_high<T>(&self, tca: &mut Tca9534Sync<T>) -> Result<(), T::Error>
+    where
+        T: SyncTransport,
+        T::Error: From<Tca9534CoreError>,
+    {
+        self.set_level(tca, PinLevel::High)
+    }
+
+    /// Drive the pin low.
+    pub fn set_low<T>(&self, tca: &mut Tca9534Sync<T>) -> Result<(), T::Error>
+    where
+        T: SyncTransport,
+        T::Error: From<Tca9534CoreError>,
+    {
+        self.set_level(tca, PinLevel::Low)
+    }
+
+    /// Toggle the pin.
+    pub fn toggle<T>(&self, tca: &mut Tca9534Sync<T>) -> Result<(), T::Error>
+    where
+        T: SyncTransport,
+        T::Error: From<Tca9534CoreError>,
+    {
+        tca.toggle_pin_output(self.pin)
+    }
+}
+
+/// Input pin of a board definition.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BoardInputPin {
+    pin: Pin,
+}
+
+impl BoardInputPin {
+    #[doc(hidden)]
+    pub const fn __new(pin: Pin) -> Self {
+        Self { pin }
+    }
+
+    /// Get the pin number.
+    pub const fn pin(&self) -> Pin {
+        self.pin
+    }
+
+    /// Read the Input Port level of the pin, after the Polarity register is applied.
+    pub fn read_level<T>(&self, tca: &mut Tca9534Sync<T>) -> Result<PinLevel, T::Error>
+    where
+        T: SyncTransport,
+        T::Error: From<Tca9534CoreError>,
+    {
+        tca.read_pin_input(self.pin)
+    }
+
+    /// Check whether the input is active.
+    ///
+    /// A pin declared `Input(Inverted)` is active low: the Polarity register inverts
+    /// it, so it reads high while the pin is electrically low.
+    pub fn is_active<T>(&self, tca: &mut Tca9534Sync<T>) -> Result<bool, T::Error>
+    where
+        T: SyncTransport,
+        T::Error: From<Tca9534CoreError>,
+    {
+        Ok(self.read_level(tca)? == PinLevel::High)
+    }
+}
+
+/// Define a board's expander pins with typed, named accessors.
+///
+/// Each entry is `name: pin => Output(High | Low)` or `name: pin => Input(Normal | Inverted)`.
+/// The macro generates a unit struct with one accessor per pin, the `OUTPUT`,
+/// `POLARITY` and `CONFIG` register values, and `apply`, which writes the whole
+/// configuration in glitch-free order (Output, Polarity, Config) with three writes.
+///
+/// ```rust
+/// use tca9534_driver_rs::tca9534_pins;
+///
+/// tca9534_pins! {
+///     pub Board {
+///         relay_main: 0 => Output(Low),
+///         relay_aux: 1 => Output(High),
+///         door_switch: 4 => Input(Inverted),
+///     }
+/// }
+///
+/// assert_eq!(Board::CONFIG, 0b1111_1100);
+/// assert_eq!(Board::OUTPUT, 0b0000_0010);
+/// assert_eq!(Board::POLARITY, 0b0001_0000);
+/// assert_eq!(Board.door_switch().pin(), 4);
+/// // board.relay_main().set_high(&mut tca)?;
+/// // board.door_switch().is_active(&mut tca)?;
+/// ```
+///
+/// Pins out of range or defined twice are rejected at compile time:
+///
+/// ```rust,compile_fail
+/// tca9534_driver_rs::tca9534_pins! {
+///     Board {
+///         relay: 3 => Output(Low),
+///         button: 3 => Input(Normal),
+///     }
+/// }
+/// ```
+///
+/// ```rust,compile_fail
+/// tca9534_driver_rs::tca9534_pins! {
+///     Board {
+///         relay: 8 => Output(Low),
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! tca9534_pins {
+    (
+        $vis:vis $board:ident {
+            $($name:ident : $pin:literal => $kind:ident($arg:ident)),+ $(,)?
+        }
+    ) => {
+        #[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+        $vis struct $board;
+
+        #[allow(dead_code)]
+        impl $board {
+            /// Initial configuration of the board.
+            pub const INITIAL: $crate::InitialConfig = $crate::board::__initial_config(&[
+                $($crate::tca9534_pins!(@def $pin, $kind($arg))),+
+            ]);
+
+            /// Output Port value of the board configuration.
+            pub const OUTPUT: u8 = Self::INITIAL.output();
+            /// Polarity Inversion register value of the board configuration.
+            pub const POLARITY: u8 = Self::INITIAL.polarity();
+            /// Configuration register value of the board configuration.
+            pub const CONFIG: u8 = Self::INITIAL.config();
+
+            /// Write the board configuration in glitch-free order (Output, Polarity, Config).
+            pub fn apply<T>(&self, tca: &mut $crate::Tca9534Sync<T>) -> Result<(), T::Error>
+            where
+                T: $crate::SyncTransport,
+            {
+                tca.apply_initial_config(&Self::INITIAL)
+            }
+
+            $($crate::tca9534_pins!(@accessor $name, $pin, $kind);)+
+        }
+
+        // Evaluate the configuration so invalid definitions fail to compile even if unused
+        const _: $crate::InitialConfig = $board::INITIAL;
+    };
+
+    (@def $pin:literal, Output($level:ident)) => {
+        $crate::board::PinDef::Output($pin, $crate::PinLevel::$level)
+    };
+    (@def $pin:literal, Input($polarity:ident)) => {
+        $crate::board::PinDef::Input($pin, $crate::PinPolarity::$polarity)
+    };
+
+    (@accessor $name:ident, $pin:literal, Output) => {
+        pub const fn $name(&self) -> $crate::board::BoardOutputPin {
+            $crate::board::BoardOutputPin::__new($pin)
+        }
+    };
+    (@accessor $name:ident, $pin:literal, Input) => {
+        pub const fn $name(&self) -> $crate::board::BoardInputPin {
+            $crate::board::BoardInputPin::__new($pin)
+        }
+    };
+}
@@ -0,0 +1,501 @@
+//! Bring-up and production test reports.
+
+use crate::error::Tca9534CoreError;
+use crate::initial_config::InitialConfig;
+use crate::registers::{Family, Pin, PinConfig, PinLevel, PinPolarity, Register, RegisterSnapshot};
+
+/// Outcome of the loopback test of one (output, input) pin pair.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LoopbackResult {
+    /// Pin driven as output.
+    pub output: Pin,
+    /// Pin read back as input.
+    pub input: Pin,
+    /// Level observed on the input while the output was driven low.
+    pub observed_low: PinLevel,
+    /// Level observed on the input while the output was driven high.
+    pub observed_high: PinLevel,
+}
+
+impl LoopbackResult {
+    /// Check whether the input followed the output in both states.
+    pub fn passed(&self) -> bool {
+        self.observed_low == PinLevel::Low && self.observed_high == PinLevel::High
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for LoopbackResult {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "P{=u8}->P{=u8}: low={} high={} {=str}",
+            self.output,
+            self.input,
+            self.observed_low,
+            self.observed_high,
+            if self.passed() { "PASS" } else { "FAIL" }
+        )
+    }
+}
+
+/// Input Port value with a flag for the all-ones/all-zeros bus failure pattern.
+///
+/// A missing or unpowered device typically reads back as 0xFF (SDA held high by the
+/// pull-up) or 0x00 (bus stuck low). Those values are also legitimate pin states, so
+/// `suspicious` is a heuristic hint to cross-check, not a proof of a bus fault.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct InputReading {
+    /// Raw Input Port value.
+    pub value: u8,
+    /// Set when the value is 0x00 or 0xFF.
+    pub suspicious: bool,
+}
+
+impl InputReading {
+    /// Classify a raw Input Port value.
+    pub const fn from_raw(value: u8) -> Self {
+        Self {
+            value,
+            suspicious: value == 0x00 || value == 0xFF,
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for InputReading {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "InputReading {{ value: {=u8:#04x}, suspicious: {=bool} }}",
+            self.value,
+            self.suspicious
+        )
+    }
+}
+
+/// Per-pair results of a loopback test, in the order the pairs were given.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LoopbackReport {
+    results: [Option<LoopbackResult>; LoopbackReport::CAPACITY],
+}
+
+#[cfg_attr(
+    not(any(feature = "embedded-hal", feature = "embedded-hal-async")),
+    allow(dead_code)
+)]
+impl LoopbackReport {
+    /// Maximum number of pairs tested in one run.
+    pub const CAPACITY: usize = 8;
+
+    pub(crate) fn new() -> Self {
+        Self {
+            results: [None; Self::CAPACITY],
+        }
+    }
+
+    pub(crate) fn push(&mut self, result: LoopbackResult) {
+        if let Some(slot) = self.results.iter_mut().find(|slot| slot.is_none()) {
+            *slot = Some(result);
+        }
+    }
+
+    /// Iterate over the pair results.
+    pub fn iter(&self) -> impl Iterator<Item = &LoopbackResult> {
+        self.results.iter().flatten()
+    }
+
+    /// Iterate over the failed pairs.
+    pub fn failures(&self) -> impl Iterator<Item = &LoopbackResult> {
+        self.iter().filter(|result| !result.passed())
+    }
+
+    /// Check whether every pair passed.
+    pub fn all_passed(&self) -> bool {
+        self.failures().next().is_none()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for LoopbackReport {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "LoopbackReport [");
+        for result in self.iter() {
+            defmt::write!(fmt, " {}", result);
+        }
+        defmt::write!(fmt, " ]");
+    }
+}
+
+/// Output states of the walking-ones then walking-zeros test pattern.
+pub const WALKING_PATTERN: [u8; 16] = {
+    let mut pattern = [0u8; 16];
+    let mut bit = 0;
+    while bit < 8 {
+        pattern[bit] = 1 << bit;
+        pattern[bit + 8] = !(1 << bit);
+        bit += 1;
+    }
+    pattern
+};
+
+/// First state of a self-test pattern whose read-back differed from the driven value.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PatternMismatch {
+    /// Value driven on the Output Port.
+    pub expected: u8,
+    /// Value read back through the Input Port, polarity compensated.
+    pub observed: u8,
+}
+
+impl PatternMismatch {
+    /// Get the mask of the pins that did not follow the driven value.
+    pub fn faulty_pins(&self) -> u8 {
+        self.expected ^ self.observed
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for PatternMismatch {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "PatternMismatch {{ expected: {=u8:#010b}, observed: {=u8:#010b} }}",
+            self.expected,
+            self.observed
+        )
+    }
+}
+
+/// Device state for a debug report, see `dump_state`.
+///
+/// `Display` renders a header line with the raw registers, then one line per pin:
+///
+/// ```text
+/// TCA9534 @ 0x20: input=0x1e output=0x0f polarity=0x30 config=0xf0
+/// P0: out out=H in=L MISMATCH
+/// P1: out out=H in=H
+/// ...
+/// P4: in  out=L in=H inverted
+/// ```
+///
+/// `in` is the Input Port bit, after the Polarity register is applied. An output pin
+/// whose electrical level differs from its commanded level is flagged `MISMATCH`
+/// (shorted or overloaded pin).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StateDump {
+    /// I2C address of the device.
+    pub address: u8,
+    /// Register values.
+    pub registers: RegisterSnapshot,
+}
+
+impl StateDump {
+    /// Length of the [`encode`](Self::encode) output.
+    pub const ENCODED_LEN: usize = 20;
+
+    /// Get the mask of the output pins whose electrical level differs from the Output Port.
+    pub fn mismatches(&self) -> u8 {
+        let regs = &self.registers;
+        !regs.config & (regs.output ^ regs.input ^ regs.polarity)
+    }
+
+    /// Encode as a fixed-layout ASCII line, without `core::fmt`.
+    ///
+    /// The layout is `@AA IXX OXX PXX CXX\n`: the address, then the Input, Output,
+    /// Polarity and Config registers, each as two uppercase hex digits, e.g.
+    /// `@20 I1E O0F P30 CF0\n`. Returns the length written ([`ENCODED_LEN`](Self::ENCODED_LEN)),
+    /// or `None` if `buf` is shorter.
+    pub fn encode(&self, buf: &mut [u8]) -> Option<usize> {
+        let buf = buf.get_mut(..Self::ENCODED_LEN)?;
+        let regs = &self.registers;
+        let fields = [
+            (b'@', self.address),
+            (b'I', regs.input),
+            (b'O', regs.output),
+            (b'P', regs.polarity),
+            (b'C', regs.config),
+        ];
+        for (chunk, (tag, value)) in buf.chunks_exact_mut(4).zip(fields) {
+            chunk[0] = tag;
+            chunk[1] = HEX[(value >> 4) as usize];
+            chunk[2] = HEX[(value & 0x0F) as usize];
+            chunk[3] = b' ';
+        }
+        buf[Self::ENCODED_LEN - 1] = b'\n';
+        Some(Self::ENCODED_LEN)
+    }
+}
+
+const HEX: &[u8; 16] = b"0123456789ABCDEF";
+
+impl core::fmt::Display for StateDump {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let regs = &self.registers;
+        let level = |value: u8, pin: u8| if value & (1 << pin) != 0 { 'H' } else { 'L' };
+
+        writeln!(
+            f,
+            "TCA9534 @ {:#04x}: input={:#04x} output={:#04x} polarity={:#04x} config={:#04x}",
+            self.address, regs.input, regs.output, regs.polarity, regs.config
+        )?;
+        for pin in 0..8u8 {
+            let direction = if regs.config & (1 << pin) != 0 {
+                "in "
+            } else {
+                "out"
+            };
+            write!(
+                f,
+                "P{}: {} out={} in={}",
+                pin,
+                direction,
+                level(regs.output, pin),
+                level(regs.input, pin)
+            )?;
+            if regs.polarity & (1 << pin) != 0 {
+                write!(f, " inverted")?;
+            }
+            if self.mismatches() & (1 << pin) != 0 {
+                write!(f, " MISMATCH")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for StateDump {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "TCA9534 @ {=u8:#04x}: {}, mismatches: {=u8:#010b}",
+            self.address,
+            self.registers,
+            self.mismatches()
+        )
+    }
+}
+
+/// Bring-up report of `describe`, e.g. to log at boot or attach to a support ticket.
+///
+/// The cached values are the ones the driver held before reading the registers (see
+/// `cached_output`); [`cache_matches`](Self::cache_matches) compares them with the device.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DeviceDescription {
+    /// Address and register values.
+    pub state: StateDump,
+    /// First family whose address window contains the address.
+    ///
+    /// The PCA9554 and PCA9554A share the windows of the TCA9534 and TCA9534A, which
+    /// are reported for them.
+    pub family: Option<Family>,
+    /// Label set with `set_label`.
+    pub label: Option<&'static str>,
+    /// Whether `init()` has completed on the driver.
+    pub initialized: bool,
+    /// Cached Output Port value.
+    pub cached_output: Option<u8>,
+    /// Cached Polarity value.
+    pub cached_polarity: Option<u8>,
+    /// Cached Config value.
+    pub cached_config: Option<u8>,
+}
+
+impl DeviceDescription {
+    /// Decode the registers for one pin.
+    ///
+    /// Fails with [`Tca9534CoreError::InvalidPin`] if `pin` is not in 0-7.
+    pub fn pin(&self, pin: Pin) -> Result<PinDescription, Tca9534CoreError> {
+        if pin > 7 {
+            return Err(Tca9534CoreError::InvalidPin);
+        }
+        let regs = &self.state.registers;
+        let bit = |value: u8| value & (1 << pin) != 0;
+        Ok(PinDescription {
+            config: if bit(regs.config) {
+                PinConfig::Input
+            } else {
+                PinConfig::Output
+            },
+            polarity: if bit(regs.polarity) {
+                PinPolarity::Inverted
+            } else {
+                PinPolarity::Normal
+            },
+            output: PinLevel::from_port(regs.output, pin),
+            input: PinLevel::from_port(regs.input, pin),
+            mismatch: bit(self.state.mismatches()),
+        })
+    }
+
+    /// Check whether the cached value of a writable register matches the device.
+    ///
+    /// Returns `None` if nothing was cached, or for the Input Port.
+    pub fn cache_matches(&self, reg: Register) -> Option<bool> {
+        let cached = match reg {
+            Register::InputPort => None,
+            Register::OutputPort => self.cached_output,
+            Register::Polarity => self.cached_polarity,
+            Register::Config => self.cached_config,
+        }?;
+        Some(cached == self.state.registers.get(reg))
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for DeviceDescription {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "{} family: {}, label: {}, initialized: {=bool}, cached output: {}, polarity: {}, config: {}",
+            self.state,
+            self.family,
+            self.label,
+            self.initialized,
+            self.cached_output,
+            self.cached_polarity,
+            self.cached_config
+        )
+    }
+}
+
+/// Decoded state of one pin, see [`DeviceDescription::pin`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PinDescription {
+    /// Direction from the Config register.
+    pub config: PinConfig,
+    /// Polarity from the Polarity register.
+    pub polarity: PinPolarity,
+    /// Commanded level from the Output Port.
+    pub output: PinLevel,
+    /// Input Port bit, after the Polarity register is applied.
+    pub input: PinLevel,
+    /// Output pin whose electrical level differs from its commanded level.
+    pub mismatch: bool,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for PinDescription {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "PinDescription {{ config: {}, polarity: {}, output: {}, input: {}, mismatch: {=bool} }}",
+            self.config,
+            self.polarity,
+            self.output,
+            self.input,
+            self.mismatch
+        )
+    }
+}
+
+/// Bits differing between the device and an expected configuration, from `check_against`.
+///
+/// Each field is a mask of the pins whose register bit differs. The Output Port is
+/// compared for every pin, including inputs, whose latch they drive once turned
+/// into outputs.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct ConfigDiff {
+    /// Differing Output Port bits.
+    pub output: u8,
+    /// Differing Polarity bits.
+    pub polarity: u8,
+    /// Differing Configuration bits.
+    pub config: u8,
+}
+
+impl ConfigDiff {
+    /// Compare the writable registers of a snapshot against a configuration.
+    pub fn new(expected: &InitialConfig, actual: &RegisterSnapshot) -> Self {
+        Self {
+            output: expected.output() ^ actual.output,
+            polarity: expected.polarity() ^ actual.polarity,
+            config: expected.config() ^ actual.config,
+        }
+    }
+
+    /// Check whether the device matches the configuration.
+    pub fn is_conformant(&self) -> bool {
+        self.pins() == 0
+    }
+
+    /// Get the mask of pins with any differing register bit.
+    pub fn pins(&self) -> u8 {
+        self.output | self.polarity | self.config
+    }
+}
+
+/// Lists the differing pins of each register, e.g.
+/// `ConfigDiff { config: P0 P3, polarity: -, output: P5 }`.
+#[cfg(feature = "defmt")]
+impl defmt::Format for ConfigDiff {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "ConfigDiff {{");
+        for (name, mask) in [
+            ("config", self.config),
+            ("polarity", self.polarity),
+            ("output", self.output),
+        ] {
+            let separator = if name == "config" { "" } else { "," };
+            defmt::write!(fmt, "{=str} {=str}:", separator, name);
+            if mask == 0 {
+                defmt::write!(fmt, " -");
+            }
+            for pin in (0..8u8).filter(|pin| mask & (1 << pin) != 0) {
+                defmt::write!(fmt, " P{=u8}", pin);
+            }
+        }
+        defmt::write!(fmt, " }}");
+    }
+}
+
+/// Per-pin summary of the cached Output Port, Polarity and Config values, for the
+/// `Debug` output of the drivers.
+///
+/// Formats as e.g. `[P0: out=H, P1: in inverted, P2: ?, ...]`; `?` marks a register
+/// whose value is not known.
+pub(crate) struct CachedPins {
+    /// Cached Output Port value.
+    pub(crate) output: Option<u8>,
+    /// Cached Polarity value.
+    pub(crate) polarity: Option<u8>,
+    /// Cached Config value.
+    pub(crate) config: Option<u8>,
+}
+
+impl CachedPins {
+    fn fmt_pin(&self, f: &mut core::fmt::Formatter<'_>, pin: Pin) -> core::fmt::Result {
+        let bit = |value: Option<u8>| value.map(|value| value & (1 << pin) != 0);
+        let level = match bit(self.output) {
+            Some(true) => 'H',
+            Some(false) => 'L',
+            None => '?',
+        };
+        match bit(self.config) {
+            Some(true) => write!(f, "P{}: in", pin)?,
+            Some(false) => write!(f, "P{}: out={}", pin, level)?,
+            None => write!(f, "P{}: ? out={}", pin, level)?,
+        }
+        match bit(self.polarity) {
+            Some(true) => write!(f, " inverted"),
+            Some(false) => Ok(()),
+            None => write!(f, " polarity=?"),
+        }
+    }
+}
+
+impl core::fmt::Debug for CachedPins {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "[")?;
+        for pin in 0..8u8 {
+            if pin > 0 {
+                write!(f, ", ")?;
+            }
+            self.fmt_pin(f, pin)?;
+        }
+        write!(f, "]")
+    }
+}
@@ -0,0 +1,70 @@
+use crate::registers::Register;
+
+/// Bytes read and written over the bus.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct ReadWriteBytes {
+    /// Bytes received from the device.
+    pub read: u32,
+    /// Bytes sent to the device, including the register command byte.
+    pub written: u32,
+}
+
+impl ReadWriteBytes {
+    /// Get the total number of bytes transferred.
+    pub fn total(&self) -> u32 {
+        self.read.saturating_add(self.written)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for ReadWriteBytes {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "ReadWriteBytes {{ read: {=u32}, written: {=u32} }}",
+            self.read,
+            self.written
+        )
+    }
+}
+
+/// Bus traffic of a driver, per register, from `byte_counts`.
+///
+/// Only data bytes are counted: every transaction also sends the address byte
+/// (twice for a register read, which is a write followed by a read), plus start
+/// and stop conditions. Transfers are counted when issued, so failed ones are
+/// included. The counters saturate at `u32::MAX`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct ByteCounts {
+    /// Traffic per register, indexed by register address.
+    pub per_register: [ReadWriteBytes; 4],
+    /// Traffic over all registers.
+    pub total: ReadWriteBytes,
+}
+
+impl ByteCounts {
+    /// Get the traffic of one register.
+    pub fn get(&self, reg: Register) -> ReadWriteBytes {
+        self.per_register[reg.addr() as usize]
+    }
+
+    /// Record a transfer to or from a register.
+    pub(crate) fn record(&mut self, reg: Register, read: u32, written: u32) {
+        for counts in [&mut self.per_register[reg.addr() as usize], &mut self.total] {
+            counts.read = counts.read.saturating_add(read);
+            counts.written = counts.written.saturating_add(written);
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for ByteCounts {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "ByteCounts {{ per_register: {}, total: {} }}",
+            self.per_register,
+            self.total
+        )
+    }
+}
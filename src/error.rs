@@ -1,14 +1,30 @@
+use crate::registers::Register;
+
 /// Core TCA9534 errors that don't depend on transport.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Tca9534CoreError {
     /// Invalid pin number (must be 0-7)
     InvalidPin,
+    /// I2C address outside the address window of the device family
+    InvalidAddress,
+    /// Operation attempted before the device was initialized (strict mode)
+    NotInitialized,
+    /// Invalid argument (e.g. too many items, or conflicting pins)
+    InvalidArgument,
+    /// Bus transaction did not complete within the operation timeout
+    Timeout,
+    /// Register read back differs from the value written
+    VerifyFailed,
+    /// Polarity register read back differs from the value written (polarity verification)
+    PolarityMismatch,
+    /// Pin not wired on the board (see `new_with_usable_pins`)
+    PinReserved,
+    /// No desired register value to reassert (see `reassert_outputs`)
+    NothingToReassert,
     // /// Invalid register address
     // InvalidRegister,
     // /// Device initialization failed
     // InitializationFailed,
-    // /// Operation timeout
-    // Timeout,
     // /// Device not responding on I2C bus
     // DeviceNotResponding,
     // /// Invalid state or configuration
@@ -24,20 +40,86 @@ pub enum Tca9534Error<I2cE = ()> {
     I2c(I2cE),
 }
 
+/// Error of `dump_state`.
+#[derive(Debug)]
+pub enum DumpError<E> {
+    /// Reading the registers failed
+    Bus(E),
+    /// The output writer failed
+    Format,
+}
+
+/// Error of `read_input_port_stable`.
+#[derive(Debug)]
+pub enum StableReadError<E> {
+    /// Reading the Input Port failed, or the arguments were invalid
+    Bus(E),
+    /// The sample budget ran out before enough consecutive reads agreed
+    NotStable {
+        /// Last value read
+        last: u8,
+    },
+}
+
+/// Direction of a failed register access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Register read
+    Read,
+    /// Register write
+    Write,
+}
+
+/// Register access during which a bus error occurred, see `last_failed_access`.
+///
+/// Displayed as e.g. `read of OutputPort`, so a log line can read
+/// `"{access} failed: {error}"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FailedAccess {
+    /// Register being accessed
+    pub register: Register,
+    /// Whether the register was being read or written
+    pub direction: Direction,
+}
+
 impl<I2cE> From<Tca9534CoreError> for Tca9534Error<I2cE> {
     fn from(err: Tca9534CoreError) -> Self {
         Tca9534Error::Core(err)
     }
 }
 
+/// Lets driver errors be used where embedded-hal digital errors are expected.
+///
+/// There is no digital error kind specific to expander failures yet, so every
+/// variant maps to [`ErrorKind::Other`](embedded_hal::digital::ErrorKind::Other).
+#[cfg(feature = "embedded-hal")]
+impl<I2cE> embedded_hal::digital::Error for Tca9534Error<I2cE>
+where
+    I2cE: core::fmt::Debug,
+{
+    fn kind(&self) -> embedded_hal::digital::ErrorKind {
+        match self {
+            Self::Core(_) => embedded_hal::digital::ErrorKind::Other,
+            Self::I2c(_) => embedded_hal::digital::ErrorKind::Other,
+        }
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for Tca9534CoreError {
     fn format(&self, fmt: defmt::Formatter) {
         match self {
             Self::InvalidPin => defmt::write!(fmt, "InvalidPin"),
+            Self::InvalidAddress => defmt::write!(fmt, "InvalidAddress"),
+            Self::NotInitialized => defmt::write!(fmt, "NotInitialized"),
+            Self::InvalidArgument => defmt::write!(fmt, "InvalidArgument"),
+            Self::Timeout => defmt::write!(fmt, "Timeout"),
+            Self::VerifyFailed => defmt::write!(fmt, "VerifyFailed"),
+            Self::PolarityMismatch => defmt::write!(fmt, "PolarityMismatch"),
+            Self::PinReserved => defmt::write!(fmt, "PinReserved"),
+            Self::NothingToReassert => defmt::write!(fmt, "NothingToReassert"),
             // Self::InvalidRegister => defmt::write!(fmt, "InvalidRegister"),
             // Self::InitializationFailed => defmt::write!(fmt, "InitializationFailed"),
-            // Self::Timeout => defmt::write!(fmt, "Timeout"),
             // Self::DeviceNotResponding => defmt::write!(fmt, "DeviceNotResponding"),
             // Self::InvalidState => defmt::write!(fmt, "InvalidState"),
         }
@@ -58,9 +140,16 @@ impl core::fmt::Display for Tca9534CoreError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Self::InvalidPin => write!(f, "Invalid pin number (must be 0-7)"),
+            Self::InvalidAddress => write!(f, "I2C address outside the device family window"),
+            Self::NotInitialized => write!(f, "Device not initialized"),
+            Self::InvalidArgument => write!(f, "Invalid argument"),
+            Self::Timeout => write!(f, "Operation timeout"),
+            Self::VerifyFailed => write!(f, "Register read back differs from the value written"),
+            Self::PolarityMismatch => write!(f, "Polarity register ignored the write"),
+            Self::PinReserved => write!(f, "Pin is reserved on this board"),
+            Self::NothingToReassert => write!(f, "No desired register value to reassert"),
             // Self::InvalidRegister => write!(f, "Invalid register address"),
             // Self::InitializationFailed => write!(f, "Device initialization failed"),
-            // Self::Timeout => write!(f, "Operation timeout"),
             // Self::DeviceNotResponding => write!(f, "Device not responding on I2C bus"),
             // Self::InvalidState => write!(f, "Invalid state or configuration"),
         }
@@ -78,3 +167,87 @@ where
         }
     }
 }
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Direction {
+    fn format(&self, fmt: defmt::Formatter) {
+        match self {
+            Self::Read => defmt::write!(fmt, "read"),
+            Self::Write => defmt::write!(fmt, "write"),
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for FailedAccess {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "{} of {}", self.direction, self.register)
+    }
+}
+
+impl core::fmt::Display for FailedAccess {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} of {:?}", self.direction, self.register)
+    }
+}
+
+impl core::fmt::Display for Direction {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Read => write!(f, "read"),
+            Self::Write => write!(f, "write"),
+        }
+    }
+}
+
+impl<E> core::fmt::Display for DumpError<E>
+where
+    E: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Bus(err) => write!(f, "{}", err),
+            Self::Format => write!(f, "Writing the state dump failed"),
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<E> defmt::Format for DumpError<E>
+where
+    E: defmt::Format,
+{
+    fn format(&self, fmt: defmt::Formatter) {
+        match self {
+            Self::Bus(err) => defmt::write!(fmt, "Bus({})", err),
+            Self::Format => defmt::write!(fmt, "Format"),
+        }
+    }
+}
+
+impl<E> core::fmt::Display for StableReadError<E>
+where
+    E: core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Bus(err) => write!(f, "{}", err),
+            Self::NotStable { last } => {
+                write!(f, "Input port not stable (last read {:#04x})", last)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<E> defmt::Format for StableReadError<E>
+where
+    E: defmt::Format,
+{
+    fn format(&self, fmt: defmt::Formatter) {
+        match self {
+            Self::Bus(err) => defmt::write!(fmt, "Bus({})", err),
+            Self::NotStable { last } => defmt::write!(fmt, "NotStable(last: {=u8:#04x})", last),
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! Directional per-pin events from successive input samples.
+
+use crate::registers::Pin;
+
+/// Transition of one input pin between two samples.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PinEvent {
+    /// The pin went from low to high.
+    RisingEdge(Pin),
+    /// The pin went from high to low.
+    FallingEdge(Pin),
+}
+
+impl PinEvent {
+    /// Get the pin number.
+    pub const fn pin(self) -> Pin {
+        match self {
+            PinEvent::RisingEdge(pin) | PinEvent::FallingEdge(pin) => pin,
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for PinEvent {
+    fn format(&self, fmt: defmt::Formatter) {
+        match self {
+            Self::RisingEdge(pin) => defmt::write!(fmt, "RisingEdge(P{=u8})", pin),
+            Self::FallingEdge(pin) => defmt::write!(fmt, "FallingEdge(P{=u8})", pin),
+        }
+    }
+}
+
+/// Events reported by one [`EdgeDetector::update`], in pin order.
+///
+/// Each pin changes at most once between two samples, so the queue never overflows.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PinEvents {
+    events: [Option<PinEvent>; PinEvents::CAPACITY],
+}
+
+impl PinEvents {
+    /// Maximum number of events reported at once, one per pin.
+    pub const CAPACITY: usize = 8;
+
+    fn new() -> Self {
+        Self {
+            events: [None; Self::CAPACITY],
+        }
+    }
+
+    /// Iterate over the events.
+    pub fn iter(&self) -> impl Iterator<Item = &PinEvent> {
+        self.events.iter().flatten()
+    }
+
+    /// Get the number of events.
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Check whether no pin changed.
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for PinEvents {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "PinEvents [");
+        for event in self.iter() {
+            defmt::write!(fmt, " {}", event);
+        }
+        defmt::write!(fmt, " ]");
+    }
+}
+
+/// Edge detector fed with raw Input Port values.
+///
+/// The first sample only sets the baseline. Pulses shorter than the polling interval
+/// are missed; use the INT line for those.
+///
+/// ```rust
+/// use tca9534_driver_rs::{EdgeDetector, PinEvent};
+///
+/// let mut edges = EdgeDetector::new();
+/// assert!(edges.update(0b0000_0001).is_empty());
+///
+/// let events = edges.update(0b0000_0010);
+/// let events: Vec<_> = events.iter().copied().collect();
+/// assert_eq!(events, [PinEvent::FallingEdge(0), PinEvent::RisingEdge(1)]);
+/// ```
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct EdgeDetector {
+    last: Option<u8>,
+}
+
+impl EdgeDetector {
+    /// Create a detector without a baseline.
+    pub const fn new() -> Self {
+        Self { last: None }
+    }
+
+    /// Create a detector with a known baseline, e.g. an initial `read_input_port()`.
+    pub const fn with_baseline(input: u8) -> Self {
+        Self { last: Some(input) }
+    }
+
+    /// Get the last sample.
+    pub const fn last(&self) -> Option<u8> {
+        self.last
+    }
+
+    /// Feed a new sample and get the transitions since the previous one.
+    pub fn update(&mut self, input: u8) -> PinEvents {
+        let mut events = PinEvents::new();
+        if let Some(last) = self.last.replace(input) {
+            let changed = last ^ input;
+            for pin in 0..8u8 {
+                if changed & (1 << pin) != 0 {
+                    events.events[pin as usize] = Some(if input & (1 << pin) != 0 {
+                        PinEvent::RisingEdge(pin)
+                    } else {
+                        PinEvent::FallingEdge(pin)
+                    });
+                }
+            }
+        }
+        events
+    }
+}
+
+/// Input Port changes found by `service_interrupt`.
+///
+/// A pin that changed and changed back between the reads of one service call is in
+/// `changed` but has the same level in `previous` and `current`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct InputChanges {
+    /// Input Port value at the end of the previous service call (or `init()`).
+    pub previous: u8,
+    /// Last Input Port value read.
+    pub current: u8,
+    /// Pins that changed between any two consecutive reads.
+    pub changed: u8,
+}
+
+impl InputChanges {
+    /// Check whether no pin changed.
+    pub fn is_empty(&self) -> bool {
+        self.changed == 0
+    }
+
+    /// Get the mask of the changed pins now high.
+    pub fn rising(&self) -> u8 {
+        self.changed & self.current
+    }
+
+    /// Get the mask of the changed pins now low.
+    pub fn falling(&self) -> u8 {
+        self.changed & !self.current
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for InputChanges {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "InputChanges {{ previous: {=u8:#010b}, current: {=u8:#010b}, changed: {=u8:#010b} }}",
+            self.previous,
+            self.current,
+            self.changed
+        )
+    }
+}
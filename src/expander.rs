@@ -0,0 +1,75 @@
+//! Driver-independent IO expander abstraction for generic application code.
+
+use crate::error::Tca9534CoreError;
+use crate::registers::{PinConfig, PinLevel};
+use crate::tca9534::Tca9534Async;
+use crate::transport::AsyncTransport;
+
+/// An asynchronous 8-bit IO expander.
+///
+/// Lets application tasks be written against any expander. The trait uses `async fn`,
+/// so it is meant for generics rather than `dyn` objects.
+///
+/// ```rust
+/// use tca9534_driver_rs::{AsyncIoExpander, PinConfig, PinLevel};
+///
+/// /// Blink an LED on any expander pin `count` times.
+/// async fn blink<E: AsyncIoExpander>(io: &mut E, pin: u8, count: usize) -> Result<(), E::Error> {
+///     io.set_pin_config(pin, PinConfig::Output).await?;
+///     for _ in 0..count {
+///         io.set_pin_output(pin, PinLevel::High).await?;
+///         // Timer::after_millis(500).await;
+///         io.set_pin_output(pin, PinLevel::Low).await?;
+///         // Timer::after_millis(500).await;
+///     }
+///     Ok(())
+/// }
+/// ```
+#[allow(async_fn_in_trait)]
+pub trait AsyncIoExpander {
+    /// Error returned by the expander operations.
+    type Error;
+
+    /// Drive an output pin to a level.
+    async fn set_pin_output(&mut self, pin: u8, level: PinLevel) -> Result<(), Self::Error>;
+
+    /// Read the level of a pin.
+    async fn read_pin_input(&mut self, pin: u8) -> Result<PinLevel, Self::Error>;
+
+    /// Configure a pin as input or output.
+    async fn set_pin_config(&mut self, pin: u8, config: PinConfig) -> Result<(), Self::Error>;
+
+    /// Write all output levels at once, one bit per pin.
+    async fn write_output_port(&mut self, value: u8) -> Result<(), Self::Error>;
+
+    /// Read all pin levels at once, one bit per pin.
+    async fn read_input_port(&mut self) -> Result<u8, Self::Error>;
+}
+
+impl<T> AsyncIoExpander for Tca9534Async<T>
+where
+    T: AsyncTransport,
+    T::Error: From<Tca9534CoreError>,
+{
+    type Error = T::Error;
+
+    async fn set_pin_output(&mut self, pin: u8, level: PinLevel) -> Result<(), Self::Error> {
+        Tca9534Async::set_pin_output(self, pin, level).await
+    }
+
+    async fn read_pin_input(&mut self, pin: u8) -> Result<PinLevel, Self::Error> {
+        Tca9534Async::read_pin_input(self, pin).await
+    }
+
+    async fn set_pin_config(&mut self, pin: u8, config: PinConfig) -> Result<(), Self::Error> {
+        Tca9534Async::set_pin_config(self, pin, config).await
+    }
+
+    async fn write_output_port(&mut self, value: u8) -> Result<(), Self::Error> {
+        Tca9534Async::write_output_port(self, value).await
+    }
+
+    async fn read_input_port(&mut self) -> Result<u8, Self::Error> {
+        Tca9534Async::read_input_port(self).await
+    }
+}
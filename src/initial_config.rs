@@ -0,0 +1,360 @@
+use crate::registers::{config, Pin, PinConfig, PinLevel, Register};
+
+/// How the init and initial configuration writes are issued.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum InitMode {
+    /// Write every register.
+    #[default]
+    Full,
+    /// Skip registers whose target value equals their power-on default.
+    ///
+    /// The skipped registers are assumed to hold that default, so use this only
+    /// right after power-on or a reset; a device left configured by a previous run
+    /// keeps its old values in the skipped registers.
+    MinimalTraffic,
+}
+
+impl InitMode {
+    /// Check whether writing `value` to `reg` can be skipped in this mode.
+    pub(crate) fn skips(self, reg: Register, value: u8) -> bool {
+        self == InitMode::MinimalTraffic && reg.power_on_default() == Some(value)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for InitMode {
+    fn format(&self, fmt: defmt::Formatter) {
+        match *self {
+            InitMode::Full => defmt::write!(fmt, "Full"),
+            InitMode::MinimalTraffic => defmt::write!(fmt, "MinimalTraffic"),
+        }
+    }
+}
+
+/// What Input Port reads return when the bus fails, see `set_degraded_policy`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum DegradedPolicy {
+    /// Return the bus error.
+    #[default]
+    Propagate,
+    /// Return the last value read successfully instead of the error.
+    ///
+    /// At most `max_age` consecutive failed reads are served from the cache; after
+    /// that, and before the first successful read, the error is returned.
+    ServeCached {
+        /// Number of consecutive failed reads that may be served from the cache.
+        max_age: u8,
+    },
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for DegradedPolicy {
+    fn format(&self, fmt: defmt::Formatter) {
+        match *self {
+            DegradedPolicy::Propagate => defmt::write!(fmt, "Propagate"),
+            DegradedPolicy::ServeCached { max_age } => {
+                defmt::write!(fmt, "ServeCached {{ max_age: {=u8} }}", max_age)
+            }
+        }
+    }
+}
+
+/// Register values for the initial setup of the expander, built in const context.
+///
+/// Pins not assigned by the builder stay inputs with normal polarity and a low
+/// output latch. Assigning a pin twice, or a pin outside 0-7, panics, which is a
+/// compile-time error when the configuration is a `const`:
+///
+/// ```rust
+/// use tca9534_driver_rs::{InitialConfig, PinLevel};
+///
+/// const SETUP: InitialConfig = InitialConfig::new()
+///     .with_outputs(&[0, 1, 2], PinLevel::High)
+///     .with_outputs(&[3], PinLevel::Low)
+///     .with_inverted(&[4, 5]);
+///
+/// assert_eq!(SETUP.config(), 0b1111_0000);
+/// assert_eq!(SETUP.output(), 0b0000_0111);
+/// assert_eq!(SETUP.polarity(), 0b0011_0000);
+/// ```
+///
+/// See [`tca9534_config!`](crate::tca9534_config) for a more compact syntax.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct InitialConfig {
+    output: u8,
+    polarity: u8,
+    config: u8,
+    /// Pins given a direction by the builder.
+    assigned: u8,
+    /// Pins given an inverted polarity by the builder.
+    inverted: u8,
+}
+
+impl InitialConfig {
+    /// Create a configuration with every pin an input, normal polarity and low output latch.
+    pub const fn new() -> Self {
+        Self {
+            output: config::ALL_OUTPUTS_LOW,
+            polarity: config::ALL_NORMAL_POLARITY,
+            config: config::ALL_INPUTS,
+            assigned: 0,
+            inverted: 0,
+        }
+    }
+
+    /// Configure pins as outputs driven to `level`.
+    ///
+    /// Panics if a pin is not in 0-7 or already has a direction.
+    pub const fn with_outputs(mut self, pins: &[Pin], level: PinLevel) -> Self {
+        let mut index = 0;
+        while index < pins.len() {
+            let mask = self.assign(pins[index]);
+            self.config &= !mask;
+            if matches!(level, PinLevel::High) {
+                self.output |= mask;
+            } else {
+                self.output &= !mask;
+            }
+            index += 1;
+        }
+        self
+    }
+
+    /// Configure pins as inputs.
+    ///
+    /// Panics if a pin is not in 0-7 or already has a direction.
+    pub const fn with_inputs(mut self, pins: &[Pin]) -> Self {
+        let mut index = 0;
+        while index < pins.len() {
+            let mask = self.assign(pins[index]);
+            self.config |= mask;
+            index += 1;
+        }
+        self
+    }
+
+    /// Invert the input polarity of pins.
+    ///
+    /// Panics if a pin is not in 0-7 or already inverted.
+    pub const fn with_inverted(mut self, pins: &[Pin]) -> Self {
+        let mut index = 0;
+        while index < pins.len() {
+            let pin = pins[index];
+            assert!(pin < 8, "invalid pin number (must be 0-7)");
+            assert!(
+                self.inverted & (1 << pin) == 0,
+                "pin inverted more than once"
+            );
+            self.inverted |= 1 << pin;
+            self.polarity |= 1 << pin;
+            index += 1;
+        }
+        self
+    }
+
+    /// Create a configuration from a per-pin table, indexed by pin number.
+    ///
+    /// The level is the Output Port bit of the pin. Input pins do not drive it, but it
+    /// is what they drive if later turned into outputs. Polarity stays normal.
+    pub const fn from_pin_table(table: &[(PinConfig, PinLevel); 8]) -> Self {
+        let mut ans = Self::new();
+        let mut pin = 0;
+        while pin < 8 {
+            let (direction, level) = table[pin];
+            if matches!(direction, PinConfig::Output) {
+                ans.config &= !(1 << pin);
+            }
+            if matches!(level, PinLevel::High) {
+                ans.output |= 1 << pin;
+            }
+            pin += 1;
+        }
+        ans.assigned = 0xFF;
+        ans
+    }
+
+    /// Get the Output Port register value.
+    pub const fn output(&self) -> u8 {
+        self.output
+    }
+
+    /// Get the Polarity Inversion register value.
+    pub const fn polarity(&self) -> u8 {
+        self.polarity
+    }
+
+    /// Get the Configuration register value.
+    pub const fn config(&self) -> u8 {
+        self.config
+    }
+
+    /// Mark a pin as having a direction and get its mask.
+    const fn assign(&mut self, pin: Pin) -> u8 {
+        assert!(pin < 8, "invalid pin number (must be 0-7)");
+        assert!(
+            self.assigned & (1 << pin) == 0,
+            "pin assigned more than once"
+        );
+        self.assigned |= 1 << pin;
+        1 << pin
+    }
+}
+
+impl Default for InitialConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for InitialConfig {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "InitialConfig {{ output: {=u8:#010b}, polarity: {=u8:#010b}, config: {=u8:#010b} }}",
+            self.output,
+            self.polarity,
+            self.config
+        )
+    }
+}
+
+/// Power-up handling of `new_with_startup`.
+///
+/// For expanders whose supply or reset comes up after the MCU: wait `settle_us`,
+/// then attempt `init()` up to `init_attempts` times (at least once), waiting
+/// `attempt_delay_us` after each failure.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct StartupOptions {
+    /// Delay before the first init attempt, in microseconds.
+    pub settle_us: u32,
+    /// Maximum number of init attempts.
+    pub init_attempts: u8,
+    /// Delay between init attempts, in microseconds.
+    pub attempt_delay_us: u32,
+}
+
+impl Default for StartupOptions {
+    /// A single attempt without delays, like `new`.
+    fn default() -> Self {
+        Self {
+            settle_us: 0,
+            init_attempts: 1,
+            attempt_delay_us: 0,
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for StartupOptions {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "StartupOptions {{ settle_us: {=u32}, init_attempts: {=u8}, attempt_delay_us: {=u32} }}",
+            self.settle_us,
+            self.init_attempts,
+            self.attempt_delay_us
+        )
+    }
+}
+
+/// Registers written by `apply_config`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct AppliedReport {
+    /// The Output Port register was written.
+    pub output: bool,
+    /// The Polarity Inversion register was written.
+    pub polarity: bool,
+    /// The Configuration register was written.
+    pub config: bool,
+}
+
+impl AppliedReport {
+    /// Get the number of registers written.
+    pub fn count(&self) -> usize {
+        [self.output, self.polarity, self.config]
+            .iter()
+            .filter(|&&written| written)
+            .count()
+    }
+
+    /// Check whether nothing was written.
+    pub fn is_empty(&self) -> bool {
+        self.count() == 0
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for AppliedReport {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "AppliedReport {{ output: {=bool}, polarity: {=bool}, config: {=bool} }}",
+            self.output,
+            self.polarity,
+            self.config
+        )
+    }
+}
+
+/// Build an [`InitialConfig`] at compile time.
+///
+/// Sections are optional but must appear in this order: `outputs` as `[pins] = high | low`
+/// groups, `inputs` as `[pins]` or `rest` (unassigned pins are inputs anyway), and
+/// `inverted` as `[pins]`.
+///
+/// ```rust
+/// use tca9534_driver_rs::{tca9534_config, InitialConfig};
+///
+/// const SETUP: InitialConfig = tca9534_config!(
+///     outputs: [0, 1, 2] = high, [3] = low,
+///     inputs: rest,
+///     inverted: [4, 5]
+/// );
+///
+/// assert_eq!(SETUP.config(), 0b1111_0000);
+/// assert_eq!(SETUP.output(), 0b0000_0111);
+/// assert_eq!(SETUP.polarity(), 0b0011_0000);
+/// ```
+///
+/// Duplicate and out-of-range pins are rejected at compile time, even in runtime context:
+///
+/// ```rust,compile_fail
+/// let setup = tca9534_driver_rs::tca9534_config!(outputs: [0, 1] = high, [1] = low);
+/// ```
+///
+/// ```rust,compile_fail
+/// let setup = tca9534_driver_rs::tca9534_config!(outputs: [0] = high, inputs: [0]);
+/// ```
+///
+/// ```rust,compile_fail
+/// let setup = tca9534_driver_rs::tca9534_config!(inverted: [8]);
+/// ```
+#[macro_export]
+macro_rules! tca9534_config {
+    (
+        $(outputs: $([$($out:literal),* $(,)?] = $level:ident),+ $(,)?)?
+        $(inputs: $inputs:tt $(,)?)?
+        $(inverted: [$($inv:literal),* $(,)?] $(,)?)?
+    ) => {{
+        const CONFIG: $crate::InitialConfig = $crate::InitialConfig::new()
+            $($(.with_outputs(&[$($out),*], $crate::tca9534_config!(@level $level)))+)?
+            $(.with_inputs($crate::tca9534_config!(@inputs $inputs)))?
+            $(.with_inverted(&[$($inv),*]))?;
+        CONFIG
+    }};
+
+    (@level high) => {
+        $crate::PinLevel::High
+    };
+    (@level low) => {
+        $crate::PinLevel::Low
+    };
+
+    (@inputs rest) => {
+        &[]
+    };
+    (@inputs [$($pin:literal),* $(,)?]) => {
+        &[$($pin),*]
+    };
+}
@@ -0,0 +1,263 @@
+//! HD44780 character LCD driven in 4-bit mode over expander pins.
+
+use embedded_hal::delay::DelayNs;
+
+use crate::error::Tca9534CoreError;
+use crate::registers::Pin;
+use crate::tca9534::Tca9534Sync;
+use crate::transport::SyncTransport;
+
+/// Clear display command; needs the long execution time.
+const CMD_CLEAR: u8 = 0x01;
+/// Return home command; needs the long execution time.
+const CMD_HOME: u8 = 0x02;
+/// Entry mode: increment, no display shift.
+const CMD_ENTRY_MODE: u8 = 0x06;
+/// Display on, cursor off, blink off.
+const CMD_DISPLAY_ON: u8 = 0x0C;
+/// Function set: 4-bit bus, 2 lines, 5x8 font.
+const CMD_FUNCTION_SET: u8 = 0x28;
+/// Set DDRAM address.
+const CMD_SET_DDRAM: u8 = 0x80;
+
+/// Enable pulse width high (tPW >= 450 ns).
+const ENABLE_PULSE_NS: u32 = 1_000;
+/// Execution time of most instructions (37 us) with margin.
+const EXEC_SHORT_US: u32 = 50;
+/// Execution time of clear/home (1.52 ms) with margin.
+const EXEC_LONG_US: u32 = 2_000;
+
+/// DDRAM address of the first column of each line.
+const ROW_OFFSETS: [u8; 4] = [0x00, 0x40, 0x14, 0x54];
+
+/// Expander pins wired to the LCD. R/W must be tied low.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LcdPins {
+    /// Register select (low: instruction, high: data).
+    pub rs: Pin,
+    /// Enable strobe.
+    pub e: Pin,
+    /// Data lines D4 to D7.
+    pub data: [Pin; 4],
+    /// Backlight control, if wired.
+    pub backlight: Option<Pin>,
+}
+
+impl LcdPins {
+    /// Get the mask of all pins driven by the adapter; the pins must be validated.
+    fn mask(&self) -> u8 {
+        let mut mask = (1 << self.rs) | (1 << self.e);
+        for pin in self.data {
+            mask |= 1 << pin;
+        }
+        if let Some(pin) = self.backlight {
+            mask |= 1 << pin;
+        }
+        mask
+    }
+
+    /// Check that every pin is in 0-7 and used once.
+    fn validate(&self) -> Result<(), Tca9534CoreError> {
+        let mut seen = 0u8;
+        let pins = [self.rs, self.e]
+            .into_iter()
+            .chain(self.data)
+            .chain(self.backlight);
+        for pin in pins {
+            if pin > 7 {
+                return Err(Tca9534CoreError::InvalidPin);
+            }
+            if seen & (1 << pin) != 0 {
+                return Err(Tca9534CoreError::InvalidArgument);
+            }
+            seen |= 1 << pin;
+        }
+        Ok(())
+    }
+}
+
+/// HD44780 4-bit bus over the synchronous driver.
+///
+/// The adapter keeps a copy of the Output Port, so each nibble costs at most three
+/// Output Port writes (data/RS setup, E high, E low) and no reads; pins not listed
+/// in [`LcdPins`] keep the value they had when the adapter was created.
+#[derive(Debug)]
+pub struct Hd44780Bus<T>
+where
+    T: SyncTransport,
+{
+    driver: Tca9534Sync<T>,
+    pins: LcdPins,
+    output: u8,
+    backlight_on: bool,
+}
+
+impl<T> Hd44780Bus<T>
+where
+    T: SyncTransport,
+    T::Error: From<Tca9534CoreError>,
+{
+    /// Take over the LCD pins: drive them low, then configure them as outputs.
+    ///
+    /// On failure the driver is handed back with the error, so the bus is not lost.
+    pub fn new(
+        mut driver: Tca9534Sync<T>,
+        pins: LcdPins,
+    ) -> Result<Self, (Tca9534Sync<T>, T::Error)> {
+        match Self::take_pins(&mut driver, &pins) {
+            Ok(output) => Ok(Self {
+                driver,
+                pins,
+                output,
+                backlight_on: false,
+            }),
+            Err(error) => Err((driver, error)),
+        }
+    }
+
+    /// Drive the LCD pins low and make them outputs; returns the Output Port written.
+    fn take_pins(driver: &mut Tca9534Sync<T>, pins: &LcdPins) -> Result<u8, T::Error> {
+        pins.validate()?;
+
+        let mask = pins.mask();
+        let output = driver.read_output_port()? & !mask;
+        driver.write_output_port(output)?;
+        let config = driver.read_port_config()? & !mask;
+        driver.set_port_config(config)?;
+        Ok(output)
+    }
+
+    /// Release the underlying driver.
+    pub fn release(self) -> Tca9534Sync<T> {
+        self.driver
+    }
+
+    /// Run the 4-bit initialization by instruction, then clear the display.
+    ///
+    /// Waits 50 ms first, so it can be called right after power-up.
+    pub fn init(&mut self, delay: &mut impl DelayNs) -> Result<(), T::Error> {
+        delay.delay_ms(50);
+
+        // Function set (8-bit) three times resynchronizes the nibble phase
+        self.write_nibble(false, 0x3, delay)?;
+        delay.delay_us(4_100);
+        self.write_nibble(false, 0x3, delay)?;
+        delay.delay_us(100);
+        self.write_nibble(false, 0x3, delay)?;
+        delay.delay_us(EXEC_SHORT_US);
+        self.write_nibble(false, 0x2, delay)?;
+        delay.delay_us(EXEC_SHORT_US);
+
+        self.command(CMD_FUNCTION_SET, delay)?;
+        self.command(CMD_DISPLAY_ON, delay)?;
+        self.clear(delay)?;
+        self.command(CMD_ENTRY_MODE, delay)
+    }
+
+    /// Switch the backlight on or off; does nothing if no backlight pin is wired.
+    pub fn set_backlight(&mut self, on: bool) -> Result<(), T::Error> {
+        self.backlight_on = on;
+        if self.pins.backlight.is_none() {
+            return Ok(());
+        }
+        let output = self.with_backlight(self.output);
+        self.write_output(output)
+    }
+
+    /// Send a nibble (low 4 bits of `nibble`) with the given RS level and strobe E.
+    pub fn write_nibble(
+        &mut self,
+        rs: bool,
+        nibble: u8,
+        delay: &mut impl DelayNs,
+    ) -> Result<(), T::Error> {
+        let mut value = self.output & !self.pins.mask();
+        if rs {
+            value |= 1 << self.pins.rs;
+        }
+        for (bit, pin) in self.pins.data.iter().enumerate() {
+            if nibble & (1 << bit) != 0 {
+                value |= 1 << pin;
+            }
+        }
+        let value = self.with_backlight(value);
+
+        // RS and data must be stable before E rises (tAS)
+        if value != self.output {
+            self.write_output(value)?;
+        }
+        self.write_output(value | (1 << self.pins.e))?;
+        delay.delay_ns(ENABLE_PULSE_NS);
+        self.write_output(value)
+    }
+
+    /// Send a byte as two nibbles, high nibble first.
+    pub fn write_byte(
+        &mut self,
+        rs: bool,
+        byte: u8,
+        delay: &mut impl DelayNs,
+    ) -> Result<(), T::Error> {
+        self.write_nibble(rs, byte >> 4, delay)?;
+        self.write_nibble(rs, byte & 0x0F, delay)
+    }
+
+    /// Send an instruction and wait for it to execute.
+    pub fn command(&mut self, command: u8, delay: &mut impl DelayNs) -> Result<(), T::Error> {
+        self.write_byte(false, command, delay)?;
+        if command == CMD_CLEAR || command == CMD_HOME {
+            delay.delay_us(EXEC_LONG_US);
+        } else {
+            delay.delay_us(EXEC_SHORT_US);
+        }
+        Ok(())
+    }
+
+    /// Clear the display and return the cursor home.
+    pub fn clear(&mut self, delay: &mut impl DelayNs) -> Result<(), T::Error> {
+        self.command(CMD_CLEAR, delay)
+    }
+
+    /// Move the cursor to a column (0-39) and row (0-3).
+    pub fn set_cursor(
+        &mut self,
+        col: u8,
+        row: u8,
+        delay: &mut impl DelayNs,
+    ) -> Result<(), T::Error> {
+        let offset = match ROW_OFFSETS.get(row as usize) {
+            Some(&offset) if col < 40 => offset,
+            _ => return Err(Tca9534CoreError::InvalidArgument.into()),
+        };
+        self.command(CMD_SET_DDRAM | (offset + col), delay)
+    }
+
+    /// Write a character code at the cursor.
+    pub fn write_char(&mut self, code: u8, delay: &mut impl DelayNs) -> Result<(), T::Error> {
+        self.write_byte(true, code, delay)?;
+        delay.delay_us(EXEC_SHORT_US);
+        Ok(())
+    }
+
+    /// Write the bytes of a string at the cursor.
+    pub fn write_str(&mut self, text: &str, delay: &mut impl DelayNs) -> Result<(), T::Error> {
+        for code in text.bytes() {
+            self.write_char(code, delay)?;
+        }
+        Ok(())
+    }
+
+    fn with_backlight(&self, value: u8) -> u8 {
+        match self.pins.backlight {
+            Some(pin) if self.backlight_on => value | (1 << pin),
+            Some(pin) => value & !(1 << pin),
+            None => value,
+        }
+    }
+
+    fn write_output(&mut self, value: u8) -> Result<(), T::Error> {
+        self.driver.write_output_port(value)?;
+        self.output = value;
+        Ok(())
+    }
+}
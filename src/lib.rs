@@ -52,8 +52,22 @@
 #![no_std]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+#[doc(hidden)]
+pub mod board;
+mod diagnostics;
+#[cfg(feature = "energy")]
+mod energy;
 mod error;
+mod events;
+#[cfg(feature = "async")]
+mod expander;
+mod initial_config;
+#[cfg(feature = "lcd")]
+mod lcd;
+mod pin_map;
+mod port;
 mod registers;
+mod seven_segment;
 mod transport;
 
 // TCA9534 driver implementations
@@ -61,16 +75,36 @@ mod tca9534;
 
 // Re-export common types
 
-pub use error::{Tca9534CoreError, Tca9534Error};
+pub use board::{BoardInputPin, BoardOutputPin};
+pub use diagnostics::*;
+#[cfg(feature = "energy")]
+pub use energy::{ByteCounts, ReadWriteBytes};
+pub use error::{
+    Direction, DumpError, FailedAccess, StableReadError, Tca9534CoreError, Tca9534Error,
+};
+pub use events::{EdgeDetector, InputChanges, PinEvent, PinEvents};
+#[cfg(feature = "async")]
+pub use expander::AsyncIoExpander;
+pub use initial_config::{AppliedReport, DegradedPolicy, InitMode, InitialConfig, StartupOptions};
+#[cfg(feature = "lcd")]
+pub use lcd::{Hd44780Bus, LcdPins};
+pub use pin_map::{NamedLevels, PinMap};
+pub use port::Port;
+#[cfg(feature = "defmt")]
+pub use port::PortBitsDefmt;
 pub use registers::*;
+pub use seven_segment::{SegMap, HEX_DIGITS};
 pub use transport::SyncTransport;
 
 #[cfg(feature = "async")]
 pub use transport::AsyncTransport;
+#[cfg(feature = "embassy-time")]
+pub use transport::Deadline;
 
 // Re-export driver implementations from tca9534 module
 
-pub use tca9534::Tca9534Sync;
+pub use tca9534::{AddressMode, FixedAddress, RuntimeAddress, Tca9534SyncDriver};
+pub use tca9534::{Batch, Tca9534Reader, Tca9534Sync, Tca9534SyncAt};
 
 #[cfg(feature = "async")]
-pub use tca9534::Tca9534Async;
+pub use tca9534::{Tca9534Async, Tca9534AsyncReader};
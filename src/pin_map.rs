@@ -0,0 +1,83 @@
+use crate::registers::{Pin, PinLevel};
+
+/// Human-readable names attached to expander pins.
+///
+/// Built in const context, so an out-of-range pin is a compile-time error:
+///
+/// ```rust,ignore
+/// const INPUTS: PinMap = PinMap::new().with(0, "DOOR").with(4, "LIMIT_SW");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PinMap<'a> {
+    names: [Option<&'a str>; 8],
+}
+
+impl<'a> PinMap<'a> {
+    /// Create an empty pin map.
+    pub const fn new() -> Self {
+        Self { names: [None; 8] }
+    }
+
+    /// Name a pin, replacing any previous name.
+    ///
+    /// Panics if `pin` is not in 0-7.
+    pub const fn with(mut self, pin: Pin, name: &'a str) -> Self {
+        assert!(pin < 8, "invalid pin number (must be 0-7)");
+        self.names[pin as usize] = Some(name);
+        self
+    }
+
+    /// Get the name of a pin.
+    pub fn name(&self, pin: Pin) -> Option<&'a str> {
+        self.names.get(pin as usize).copied().flatten()
+    }
+
+    /// Iterate over the named pins in pin order.
+    pub fn iter(&self) -> impl Iterator<Item = (Pin, &'a str)> + '_ {
+        (0..8u8).filter_map(|pin| self.name(pin).map(|name| (pin, name)))
+    }
+
+    /// Decode a port value into the levels of the named pins.
+    pub fn levels(&self, port_value: u8) -> NamedLevels<'a> {
+        let mut levels = [None; 8];
+        for (pin, name) in self.iter() {
+            levels[pin as usize] = Some((name, PinLevel::from_port(port_value, pin)));
+        }
+        NamedLevels { levels }
+    }
+}
+
+impl Default for PinMap<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Levels of the pins named in a [`PinMap`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct NamedLevels<'a> {
+    levels: [Option<(&'a str, PinLevel)>; 8],
+}
+
+impl<'a> NamedLevels<'a> {
+    /// Get the level of a pin by name.
+    pub fn get(&self, name: &str) -> Option<PinLevel> {
+        self.iter()
+            .find(|&(pin_name, _)| pin_name == name)
+            .map(|(_, level)| level)
+    }
+
+    /// Iterate over the name/level pairs in pin order.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, PinLevel)> + '_ {
+        self.levels.iter().flatten().copied()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for NamedLevels<'_> {
+    fn format(&self, fmt: defmt::Formatter) {
+        for (name, level) in self.iter() {
+            defmt::write!(fmt, "{=str}={} ", name, level);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, Not, Shl, Shr};
+
+use crate::registers::Pin;
+
+/// Set of expander pins, one bit per pin.
+///
+/// Keeps pin masks type-distinct from plain register values while allowing them
+/// to be composed with the usual bit operators:
+///
+/// ```rust,ignore
+/// let leds = Port::pin(0) | Port::pin(1) | Port::pin(2);
+/// tca9534.write_output_masked(leds, !Port::NONE)?;
+/// ```
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Port(pub u8);
+
+impl Port {
+    /// No pins.
+    pub const NONE: Port = Port(0x00);
+    /// All 8 pins.
+    pub const ALL: Port = Port(0xFF);
+
+    /// Get the mask of a single pin.
+    ///
+    /// Panics if `pin` is not in 0-7.
+    pub const fn pin(pin: Pin) -> Self {
+        assert!(pin < 8, "invalid pin number (must be 0-7)");
+        Port(1 << pin)
+    }
+
+    /// Get the raw bit value.
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Check whether a pin is part of the set.
+    pub const fn contains(self, pin: Pin) -> bool {
+        pin < 8 && self.0 & (1 << pin) != 0
+    }
+
+    /// Check whether no pin is part of the set.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Iterate over the pins of the set in ascending order.
+    pub fn pins(self) -> impl Iterator<Item = Pin> {
+        (0..8u8).filter(move |&pin| self.contains(pin))
+    }
+}
+
+impl From<u8> for Port {
+    fn from(value: u8) -> Self {
+        Port(value)
+    }
+}
+
+impl From<Port> for u8 {
+    fn from(port: Port) -> Self {
+        port.0
+    }
+}
+
+impl BitOr for Port {
+    type Output = Port;
+
+    fn bitor(self, rhs: Port) -> Port {
+        Port(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Port {
+    fn bitor_assign(&mut self, rhs: Port) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitAnd for Port {
+    type Output = Port;
+
+    fn bitand(self, rhs: Port) -> Port {
+        Port(self.0 & rhs.0)
+    }
+}
+
+impl BitAndAssign for Port {
+    fn bitand_assign(&mut self, rhs: Port) {
+        self.0 &= rhs.0;
+    }
+}
+
+impl BitXor for Port {
+    type Output = Port;
+
+    fn bitxor(self, rhs: Port) -> Port {
+        Port(self.0 ^ rhs.0)
+    }
+}
+
+impl Not for Port {
+    type Output = Port;
+
+    fn not(self) -> Port {
+        Port(!self.0)
+    }
+}
+
+/// Shifting by 8 or more clears the set instead of overflowing.
+impl Shl<u8> for Port {
+    type Output = Port;
+
+    fn shl(self, rhs: u8) -> Port {
+        Port(self.0.checked_shl(rhs.into()).unwrap_or(0))
+    }
+}
+
+/// Shifting by 8 or more clears the set instead of overflowing.
+impl Shr<u8> for Port {
+    type Output = Port;
+
+    fn shr(self, rhs: u8) -> Port {
+        Port(self.0.checked_shr(rhs.into()).unwrap_or(0))
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Port {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "Port({=u8:#010b})", self.0)
+    }
+}
+
+/// Port value formatted with defmt as labeled bits, e.g. `{0=H 1=L 2=L 3=L 4=L 5=L 6=L 7=H}`.
+///
+/// Wrap raw register values in log lines:
+///
+/// ```rust,ignore
+/// defmt::info!("inputs: {}", PortBitsDefmt(tca9534.read_input_port()?));
+/// ```
+#[cfg(feature = "defmt")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PortBitsDefmt(pub u8);
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for PortBitsDefmt {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(fmt, "{{");
+        for pin in 0..8u8 {
+            let level = if self.0 & (1 << pin) != 0 { "H" } else { "L" };
+            let separator = if pin == 0 { "" } else { " " };
+            defmt::write!(fmt, "{=str}{=u8}={=str}", separator, pin, level);
+        }
+        defmt::write!(fmt, "}}");
+    }
+}
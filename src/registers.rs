@@ -1,9 +1,11 @@
-/// TCA9534 register definitions.
-///
-/// Based on TCA9534 datasheet: <https://www.ti.com/lit/ds/symlink/tca9534.pdf>
+//! TCA9534 register definitions.
+//!
+//! Based on TCA9534 datasheet: <https://www.ti.com/lit/ds/symlink/tca9534.pdf>
+
+use crate::error::Tca9534CoreError;
 
 /// Register enumeration.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Register {
     /// Input port register (0x00) - Read only.
     ///
@@ -35,8 +37,50 @@ impl Register {
     pub fn addr(self) -> u8 {
         self as u8
     }
+
+    /// Get the register at a command byte address.
+    pub fn from_addr(addr: u8) -> Option<Self> {
+        match addr {
+            0x00 => Some(Register::InputPort),
+            0x01 => Some(Register::OutputPort),
+            0x02 => Some(Register::Polarity),
+            0x03 => Some(Register::Config),
+            _ => None,
+        }
+    }
+
+    /// Get the register at the next-higher address, or `None` after the Config register.
+    ///
+    /// Use with `core::iter::successors(Some(Register::InputPort), |r| r.next())` to walk
+    /// all registers in address order. The TCA9534 does not auto-increment its register
+    /// pointer, so the driver still reads each register with its own transaction.
+    pub fn next(self) -> Option<Self> {
+        Self::from_addr(self.addr() + 1)
+    }
+
+    /// Get the power-on reset value of the register, per the datasheet.
+    ///
+    /// Returns `None` for the Input Port register, which reflects the pin levels.
+    pub fn power_on_default(self) -> Option<u8> {
+        match self {
+            Register::InputPort => None,
+            Register::OutputPort => Some(config::POR_OUTPUT_DEFAULT),
+            Register::Polarity => Some(config::ALL_NORMAL_POLARITY),
+            Register::Config => Some(config::ALL_INPUTS),
+        }
+    }
 }
 
+/// Register command byte addresses, indexed by register ordinal.
+///
+/// The addresses are contiguous from 0x00, so `REGISTER_ADDRS[i] == i as u8`.
+pub const REGISTER_ADDRS: [u8; 4] = [
+    Register::InputPort as u8,
+    Register::OutputPort as u8,
+    Register::Polarity as u8,
+    Register::Config as u8,
+];
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for Register {
     fn format(&self, fmt: defmt::Formatter) {
@@ -49,8 +93,63 @@ impl defmt::Format for Register {
     }
 }
 
+/// Order in which `write_output_staggered` changes the output pins.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StaggerOrder<'a> {
+    /// Pin 0 first.
+    LsbFirst,
+    /// Pin 7 first.
+    MsbFirst,
+    /// The listed pins, first to last; every pin that changes must be listed.
+    Custom(&'a [Pin]),
+}
+
+#[cfg(any(
+    feature = "embedded-hal",
+    all(feature = "async", feature = "embedded-hal-async")
+))]
+impl StaggerOrder<'_> {
+    /// Get the pin at `index` in the order, if any.
+    pub(crate) fn nth(self, index: usize) -> Option<Pin> {
+        match self {
+            StaggerOrder::LsbFirst => (index < 8).then_some(index as Pin),
+            StaggerOrder::MsbFirst => (index < 8).then(|| 7 - index as Pin),
+            StaggerOrder::Custom(pins) => pins.get(index).copied(),
+        }
+    }
+
+    /// Get the mask of the pins in the order, validating a custom one.
+    pub(crate) fn mask(self) -> Result<u8, crate::error::Tca9534CoreError> {
+        let StaggerOrder::Custom(pins) = self else {
+            return Ok(0xFF);
+        };
+        if pins.len() > 8 {
+            return Err(crate::error::Tca9534CoreError::InvalidArgument);
+        }
+        let mut mask = 0;
+        for &pin in pins {
+            if pin > 7 {
+                return Err(crate::error::Tca9534CoreError::InvalidPin);
+            }
+            mask |= 1 << pin;
+        }
+        Ok(mask)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for StaggerOrder<'_> {
+    fn format(&self, fmt: defmt::Formatter) {
+        match *self {
+            StaggerOrder::LsbFirst => defmt::write!(fmt, "LsbFirst"),
+            StaggerOrder::MsbFirst => defmt::write!(fmt, "MsbFirst"),
+            StaggerOrder::Custom(pins) => defmt::write!(fmt, "Custom({=[u8]})", pins),
+        }
+    }
+}
+
 /// Pin configuration (direction).
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum PinConfig {
     /// Pin configured as input (high impedance) - default.
     Input = 1,
@@ -65,6 +164,19 @@ impl PinConfig {
     }
 }
 
+/// Decode a config bit value (1 = input, 0 = output).
+impl TryFrom<u8> for PinConfig {
+    type Error = Tca9534CoreError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(PinConfig::Input),
+            0 => Ok(PinConfig::Output),
+            _ => Err(Tca9534CoreError::InvalidArgument),
+        }
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for PinConfig {
     fn format(&self, fmt: defmt::Formatter) {
@@ -76,7 +188,7 @@ impl defmt::Format for PinConfig {
 }
 
 /// Pin polarity setting.
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum PinPolarity {
     /// Normal polarity (default) - GPIO register bit reflects same value on the input pin.
     Normal = 0,
@@ -91,6 +203,19 @@ impl PinPolarity {
     }
 }
 
+/// Decode a polarity bit value (0 = normal, 1 = inverted).
+impl TryFrom<u8> for PinPolarity {
+    type Error = Tca9534CoreError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(PinPolarity::Normal),
+            1 => Ok(PinPolarity::Inverted),
+            _ => Err(Tca9534CoreError::InvalidArgument),
+        }
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for PinPolarity {
     fn format(&self, fmt: defmt::Formatter) {
@@ -102,7 +227,7 @@ impl defmt::Format for PinPolarity {
 }
 
 /// Pin logic level.
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum PinLevel {
     /// Logic low (0V).
     Low = 0,
@@ -115,6 +240,32 @@ impl PinLevel {
     pub fn bits(self) -> u8 {
         self as u8
     }
+
+    /// Get the level of a pin from a port value.
+    ///
+    /// `pin` must be in 0-7, which the driver checks before calling this; a larger pin
+    /// panics in debug builds.
+    pub fn from_port(port_value: u8, pin: Pin) -> Self {
+        debug_assert!(pin < 8, "invalid pin number (must be 0-7)");
+        if (port_value >> pin) & 0x01 == 0 {
+            PinLevel::Low
+        } else {
+            PinLevel::High
+        }
+    }
+}
+
+/// Decode a level bit value (0 = low, 1 = high).
+impl TryFrom<u8> for PinLevel {
+    type Error = Tca9534CoreError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(PinLevel::Low),
+            1 => Ok(PinLevel::High),
+            _ => Err(Tca9534CoreError::InvalidArgument),
+        }
+    }
 }
 
 #[cfg(feature = "defmt")]
@@ -127,6 +278,116 @@ impl defmt::Format for PinLevel {
     }
 }
 
+/// Logical state of a pin, independent of its electrical level.
+///
+/// Kept distinct from [`PinLevel`] so a logical reading cannot be compared against an
+/// electrical constant by mistake.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LogicalLevel {
+    /// The signal is asserted.
+    Active,
+    /// The signal is deasserted.
+    Inactive,
+}
+
+impl LogicalLevel {
+    /// Get the logical state of an electrical level, given the level at which the signal is active.
+    pub fn from_level(level: PinLevel, active_level: PinLevel) -> Self {
+        if level == active_level {
+            LogicalLevel::Active
+        } else {
+            LogicalLevel::Inactive
+        }
+    }
+
+    /// Get the electrical level producing this logical state.
+    pub fn to_level(self, active_level: PinLevel) -> PinLevel {
+        match (self, active_level) {
+            (LogicalLevel::Active, level) => level,
+            (LogicalLevel::Inactive, PinLevel::High) => PinLevel::Low,
+            (LogicalLevel::Inactive, PinLevel::Low) => PinLevel::High,
+        }
+    }
+
+    /// Check whether the signal is asserted.
+    pub fn is_active(self) -> bool {
+        self == LogicalLevel::Active
+    }
+}
+
+impl From<bool> for LogicalLevel {
+    fn from(active: bool) -> Self {
+        if active {
+            LogicalLevel::Active
+        } else {
+            LogicalLevel::Inactive
+        }
+    }
+}
+
+impl From<LogicalLevel> for bool {
+    fn from(level: LogicalLevel) -> Self {
+        level.is_active()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for LogicalLevel {
+    fn format(&self, fmt: defmt::Formatter) {
+        match *self {
+            LogicalLevel::Active => defmt::write!(fmt, "Active"),
+            LogicalLevel::Inactive => defmt::write!(fmt, "Inactive"),
+        }
+    }
+}
+
+/// Values of all four registers read at one point in time.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegisterSnapshot {
+    /// Input Port register value.
+    pub input: u8,
+    /// Output Port register value.
+    pub output: u8,
+    /// Polarity Inversion register value.
+    pub polarity: u8,
+    /// Configuration register value.
+    pub config: u8,
+}
+
+impl RegisterSnapshot {
+    /// Get the value of a register.
+    pub fn get(&self, reg: Register) -> u8 {
+        match reg {
+            Register::InputPort => self.input,
+            Register::OutputPort => self.output,
+            Register::Polarity => self.polarity,
+            Register::Config => self.config,
+        }
+    }
+
+    /// Compare the writable registers (Output, Polarity, Config) of two snapshots.
+    pub fn writable_eq(&self, other: &RegisterSnapshot) -> bool {
+        self.output == other.output
+            && self.polarity == other.polarity
+            && self.config == other.config
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for RegisterSnapshot {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "RegisterSnapshot {{ input: {=u8:#04x}, output: {=u8:#04x}, polarity: {=u8:#04x}, config: {=u8:#04x} }}",
+            self.input,
+            self.output,
+            self.polarity,
+            self.config
+        )
+    }
+}
+
 /// Pin number type (0-7).
 pub type Pin = u8;
 
@@ -152,6 +413,40 @@ pub mod config {
 
     /// All outputs high.
     pub const ALL_OUTPUTS_HIGH: u8 = 0xFF;
+
+    /// Power-on reset value of the Output Port register (all high), per the datasheet.
+    ///
+    /// The driver deliberately differs: `init()` writes [`ALL_OUTPUTS_LOW`] so that a pin
+    /// switched to output starts low. Use `new_with_por_output` to keep the hardware default.
+    pub const POR_OUTPUT_DEFAULT: u8 = 0xFF;
+}
+
+/// Register writes performed by `init()`, in order.
+///
+/// Config is written first so that no pin is driven while the other registers change.
+pub const INIT_SEQUENCE: &[(Register, u8)] = &[
+    (Register::Config, config::ALL_INPUTS),
+    (Register::OutputPort, config::ALL_OUTPUTS_LOW),
+    (Register::Polarity, config::ALL_NORMAL_POLARITY),
+];
+
+/// General-call command byte requesting a software reset.
+pub const GENERAL_CALL_RESET: u8 = 0x06;
+
+/// Datasheet register defaults.
+pub mod defaults {
+    use super::{config, RegisterSnapshot};
+
+    /// Register values after power-on reset.
+    ///
+    /// The Input Port register reflects the pin levels and has no defined default;
+    /// it is reported as 0x00 here and should be ignored when comparing.
+    pub const POWER_ON_SNAPSHOT: RegisterSnapshot = RegisterSnapshot {
+        input: 0x00,
+        output: config::POR_OUTPUT_DEFAULT,
+        polarity: config::ALL_NORMAL_POLARITY,
+        config: config::ALL_INPUTS,
+    };
 }
 
 /// Common I2C addresses for TCA9534 based on A2, A1, A0 pins.
@@ -172,4 +467,77 @@ pub mod addresses {
     pub const ADDR_110: u8 = 0x26;
     /// A2=1, A1=1, A0=1.
     pub const ADDR_111: u8 = 0x27;
+
+    /// I2C general-call address, see [`GENERAL_CALL_RESET`](super::GENERAL_CALL_RESET).
+    pub const GENERAL_CALL: u8 = 0x00;
+
+    /// Compute the TCA9534 address from the A2, A1, A0 strap levels (`true` = tied high).
+    pub const fn for_straps(a2: bool, a1: bool, a0: bool) -> u8 {
+        for_straps_in(super::Family::Tca9534, a2, a1, a0)
+    }
+
+    /// Compute the address of a device of the given family from its strap levels.
+    pub const fn for_straps_in(family: super::Family, a2: bool, a1: bool, a0: bool) -> u8 {
+        family.base_address() | ((a2 as u8) << 2) | ((a1 as u8) << 1) | (a0 as u8)
+    }
+}
+
+/// Footprint-compatible parts sharing the TCA9534 register map.
+///
+/// The parts only differ in the I2C address window selected by the A2, A1, A0 pins.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Family {
+    /// TCA9534, addresses 0x20-0x27.
+    Tca9534,
+    /// TCA9534A, addresses 0x38-0x3F.
+    Tca9534A,
+    /// PCA9554, addresses 0x20-0x27.
+    Pca9554,
+    /// PCA9554A, addresses 0x38-0x3F.
+    Pca9554A,
+}
+
+impl Family {
+    /// All supported families, low address window first.
+    pub const ALL: [Family; 4] = [
+        Family::Tca9534,
+        Family::Pca9554,
+        Family::Tca9534A,
+        Family::Pca9554A,
+    ];
+
+    /// Get the base address (A2=0, A1=0, A0=0) of the family.
+    pub const fn base_address(self) -> u8 {
+        match self {
+            Family::Tca9534 | Family::Pca9554 => 0x20,
+            Family::Tca9534A | Family::Pca9554A => 0x38,
+        }
+    }
+
+    /// Get the default address (A2=0, A1=0, A0=0) of the family.
+    pub const fn default_address(self) -> u8 {
+        self.base_address()
+    }
+
+    /// Check whether an address lies in the address window of the family.
+    pub const fn contains(self, address: u8) -> bool {
+        address >= self.base_address() && address <= self.base_address() + 7
+    }
+
+    /// Iterate over the 8 addresses of the family.
+    pub fn addresses(self) -> impl Iterator<Item = u8> {
+        self.base_address()..=self.base_address() + 7
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Family {
+    fn format(&self, fmt: defmt::Formatter) {
+        match *self {
+            Family::Tca9534 => defmt::write!(fmt, "TCA9534"),
+            Family::Tca9534A => defmt::write!(fmt, "TCA9534A"),
+            Family::Pca9554 => defmt::write!(fmt, "PCA9554"),
+            Family::Pca9554A => defmt::write!(fmt, "PCA9554A"),
+        }
+    }
 }
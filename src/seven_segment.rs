@@ -0,0 +1,77 @@
+use crate::registers::Pin;
+
+/// Segments lit for the hexadecimal digits 0-F, bit 0 = segment a ... bit 6 = segment g.
+pub const HEX_DIGITS: [u8; 16] = [
+    0b011_1111, // 0
+    0b000_0110, // 1
+    0b101_1011, // 2
+    0b100_1111, // 3
+    0b110_0110, // 4
+    0b110_1101, // 5
+    0b111_1101, // 6
+    0b000_0111, // 7
+    0b111_1111, // 8
+    0b110_1111, // 9
+    0b111_0111, // A
+    0b111_1100, // b
+    0b011_1001, // C
+    0b101_1110, // d
+    0b111_1001, // E
+    0b111_0001, // F
+];
+
+/// Wiring of a single 7-segment digit to the expander pins.
+///
+/// ```rust,ignore
+/// // a-g on P0-P6, decimal point on P7, common anode
+/// const DIGIT: SegMap = SegMap::new([0, 1, 2, 3, 4, 5, 6], 7, true);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SegMap {
+    /// Pins driving segments a to g.
+    pub segments: [Pin; 7],
+    /// Pin driving the decimal point.
+    pub dp: Pin,
+    /// Common anode display: a segment is lit by driving its pin low.
+    pub common_anode: bool,
+}
+
+impl SegMap {
+    /// Create a segment mapping.
+    ///
+    /// Panics if a pin is not in 0-7.
+    pub const fn new(segments: [Pin; 7], dp: Pin, common_anode: bool) -> Self {
+        let mut index = 0;
+        while index < 7 {
+            assert!(segments[index] < 8, "invalid pin number (must be 0-7)");
+            index += 1;
+        }
+        assert!(dp < 8, "invalid pin number (must be 0-7)");
+        Self {
+            segments,
+            dp,
+            common_anode,
+        }
+    }
+
+    /// Get the Output Port value displaying a hexadecimal digit (0-15).
+    ///
+    /// Returns `None` if the digit is above 15 or a pin in the (public) fields is not
+    /// in 0-7.
+    pub fn encode(&self, digit: u8, dp: bool) -> Option<u8> {
+        let lit = *HEX_DIGITS.get(digit as usize)?;
+        if self.dp > 7 || self.segments.iter().any(|&pin| pin > 7) {
+            return None;
+        }
+        let mut value = 0u8;
+        for (segment, &pin) in self.segments.iter().enumerate() {
+            if lit & (1 << segment) != 0 {
+                value |= 1 << pin;
+            }
+        }
+        if dp {
+            value |= 1 << self.dp;
+        }
+        Some(if self.common_anode { !value } else { value })
+    }
+}
@@ -0,0 +1,201 @@
+use super::tca9534_sync::Tca9534;
+use super::typed::{AddressMode, RuntimeAddress};
+use crate::error::*;
+use crate::registers::*;
+use crate::transport::SyncTransport;
+
+/// In-memory copy of a writable register.
+#[derive(Debug, Default)]
+struct Shadow {
+    value: Option<u8>,
+    dirty: bool,
+}
+
+impl Shadow {
+    fn set(&mut self, value: u8) {
+        self.value = Some(value);
+        self.dirty = true;
+    }
+}
+
+/// Batch of register updates, written to the device on `flush()` or drop.
+///
+/// Obtained from [`Tca9534::begin_batch`]. Pin and port methods only update in-memory
+/// shadows of the Output, Polarity and Config registers; a shadow is loaded from the
+/// device the first time a single pin of it is modified. `flush()` then writes each
+/// modified register once, in glitch-free order (Output, Polarity, Config).
+///
+/// Dropping the batch flushes pending writes and discards any error; call `flush()`
+/// explicitly to observe failures.
+pub struct Batch<'a, T, A = RuntimeAddress>
+where
+    T: SyncTransport,
+    A: AddressMode,
+{
+    driver: &'a mut Tca9534<T, A>,
+    output: Shadow,
+    polarity: Shadow,
+    config: Shadow,
+}
+
+/// Shows the pending shadows; the borrowed driver is left out.
+impl<T, A> core::fmt::Debug for Batch<'_, T, A>
+where
+    T: SyncTransport,
+    A: AddressMode,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Batch")
+            .field("output", &self.output)
+            .field("polarity", &self.polarity)
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, T, A> Batch<'a, T, A>
+where
+    T: SyncTransport,
+    A: AddressMode,
+{
+    pub(super) fn new(driver: &'a mut Tca9534<T, A>) -> Self {
+        Self {
+            driver,
+            output: Shadow::default(),
+            polarity: Shadow::default(),
+            config: Shadow::default(),
+        }
+    }
+
+    fn shadow(&mut self, reg: Register) -> &mut Shadow {
+        match reg {
+            Register::OutputPort => &mut self.output,
+            Register::Polarity => &mut self.polarity,
+            // Only writable registers are shadowed
+            _ => &mut self.config,
+        }
+    }
+
+    /// Get the shadowed value of a register, reading it from the device if not loaded yet.
+    fn load(&mut self, reg: Register) -> Result<u8, T::Error> {
+        if let Some(value) = self.shadow(reg).value {
+            return Ok(value);
+        }
+
+        let value = self.driver.read_register(reg)?;
+        self.shadow(reg).value = Some(value);
+        Ok(value)
+    }
+
+    /// Update a single bit of a shadowed register.
+    fn update_bit(&mut self, reg: Register, pin: u8, set: bool) -> Result<(), T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        self.driver.check_pin(pin)?;
+
+        let mut value = self.load(reg)?;
+        if set {
+            value |= 1 << pin;
+        } else {
+            value &= !(1 << pin);
+        }
+        self.shadow(reg).set(value);
+        Ok(())
+    }
+
+    /// Set a specific output pin.
+    pub fn set_pin_output(&mut self, pin: u8, level: PinLevel) -> Result<(), T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        self.update_bit(Register::OutputPort, pin, level == PinLevel::High)
+    }
+
+    /// Toggle a specific output pin.
+    pub fn toggle_pin_output(&mut self, pin: u8) -> Result<(), T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        self.driver.check_pin(pin)?;
+
+        let value = self.load(Register::OutputPort)?;
+        self.output.set(value ^ (1 << pin));
+        Ok(())
+    }
+
+    /// Write all output pins at once.
+    pub fn write_output_port(&mut self, value: u8) {
+        self.output.set(value);
+    }
+
+    /// Configure pin direction (input/output).
+    pub fn set_pin_config(&mut self, pin: u8, config: PinConfig) -> Result<(), T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        self.update_bit(Register::Config, pin, config == PinConfig::Input)
+    }
+
+    /// Configure all pins direction at once.
+    pub fn set_port_config(&mut self, config: u8) {
+        self.config.set(config);
+    }
+
+    /// Set pin polarity (normal/inverted).
+    pub fn set_pin_polarity(&mut self, pin: u8, polarity: PinPolarity) -> Result<(), T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        self.update_bit(Register::Polarity, pin, polarity == PinPolarity::Inverted)
+    }
+
+    /// Configure all pins polarity at once.
+    pub fn set_port_polarity(&mut self, polarity: u8) {
+        self.polarity.set(polarity);
+    }
+
+    /// Check whether any modification is waiting for a flush.
+    ///
+    /// Set by every pin and port method, cleared once the register is flushed.
+    pub fn is_dirty(&self) -> bool {
+        self.output.dirty || self.polarity.dirty || self.config.dirty
+    }
+
+    /// Write all modified registers to the device.
+    pub fn flush(&mut self) -> Result<(), T::Error> {
+        for reg in [Register::OutputPort, Register::Polarity, Register::Config] {
+            let shadow = self.shadow(reg);
+            if let (true, Some(value)) = (shadow.dirty, shadow.value) {
+                self.driver.write_register(reg, value)?;
+                self.shadow(reg).dirty = false;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the Output Port shadow, unless the device already holds it.
+    ///
+    /// The shadow is compared with the last value the driver wrote (or read back), not
+    /// with the last `flush`, so this issues at most one write and none when the
+    /// pending changes cancel out. Polarity and Config changes stay pending.
+    pub fn flush_output(&mut self) -> Result<(), T::Error> {
+        if let Some(value) = self.output.value {
+            if self.driver.cached_output() != Some(value) {
+                self.driver.write_register(Register::OutputPort, value)?;
+            }
+            self.output.dirty = false;
+        }
+        Ok(())
+    }
+}
+
+impl<T, A> Drop for Batch<'_, T, A>
+where
+    T: SyncTransport,
+    A: AddressMode,
+{
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
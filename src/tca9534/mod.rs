@@ -1,13 +1,28 @@
 // Synchronous implementation (always available).
 mod tca9534_sync;
 
+// Batched register updates for the synchronous driver.
+mod batch;
+
+// Read-only views of the drivers.
+mod reader;
+
+// Synchronous driver with the address in its type.
+mod typed;
+
 // Asynchronous implementation (feature-gated).
 #[cfg(feature = "async")]
 mod tca9534_async;
 
 // Re-export driver implementations.
 
-pub use tca9534_sync::Tca9534 as Tca9534Sync;
+pub use batch::Batch;
+pub use reader::Tca9534Reader;
+pub use tca9534_sync::Tca9534 as Tca9534SyncDriver;
+pub use typed::{AddressMode, FixedAddress, RuntimeAddress};
+pub use typed::{Tca9534At as Tca9534SyncAt, Tca9534Runtime as Tca9534Sync};
 
+#[cfg(feature = "async")]
+pub use reader::Tca9534AsyncReader;
 #[cfg(feature = "async")]
 pub use tca9534_async::Tca9534 as Tca9534Async;
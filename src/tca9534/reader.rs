@@ -0,0 +1,174 @@
+#[cfg(feature = "async")]
+use super::tca9534_async::Tca9534 as Tca9534Async;
+use super::tca9534_sync::Tca9534;
+use super::typed::{AddressMode, RuntimeAddress};
+use crate::diagnostics::StateDump;
+use crate::error::*;
+use crate::registers::*;
+#[cfg(feature = "async")]
+use crate::transport::AsyncTransport;
+use crate::transport::SyncTransport;
+
+/// Read-only view of a synchronous driver.
+///
+/// Obtained from [`Tca9534::reader`], for tasks that may read the expander but must
+/// not change its outputs or configuration. Only read methods are exposed:
+///
+/// ```rust,compile_fail
+/// # fn check<T: tca9534_driver_rs::SyncTransport>(tca: &mut tca9534_driver_rs::Tca9534Sync<T>) {
+/// let mut reader = tca.reader();
+/// reader.write_output_port(0xFF);
+/// # }
+/// ```
+///
+/// ```rust,compile_fail
+/// # fn check<T: tca9534_driver_rs::SyncTransport>(tca: &mut tca9534_driver_rs::Tca9534Sync<T>) {
+/// let mut reader = tca.reader();
+/// reader.set_port_config(0x00);
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Tca9534Reader<'a, T, A = RuntimeAddress>
+where
+    A: AddressMode,
+{
+    driver: &'a mut Tca9534<T, A>,
+}
+
+impl<'a, T, A> Tca9534Reader<'a, T, A>
+where
+    T: SyncTransport,
+    A: AddressMode,
+{
+    pub(super) fn new(driver: &'a mut Tca9534<T, A>) -> Self {
+        Self { driver }
+    }
+
+    /// Get the I2C address of the device.
+    pub fn address(&self) -> u8 {
+        self.driver.address()
+    }
+
+    /// Read all input pins at once.
+    pub fn read_input_port(&mut self) -> Result<u8, T::Error> {
+        self.driver.read_input_port()
+    }
+
+    /// Read a specific input pin.
+    pub fn read_pin_input(&mut self, pin: u8) -> Result<PinLevel, T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        self.driver.read_pin_input(pin)
+    }
+
+    /// Read the Output Port register.
+    pub fn read_output_port(&mut self) -> Result<u8, T::Error> {
+        self.driver.read_output_port()
+    }
+
+    /// Read port configuration.
+    pub fn read_port_config(&mut self) -> Result<u8, T::Error> {
+        self.driver.read_port_config()
+    }
+
+    /// Read port polarity configuration.
+    pub fn read_port_polarity(&mut self) -> Result<u8, T::Error> {
+        self.driver.read_port_polarity()
+    }
+
+    /// Read all four registers.
+    pub fn read_snapshot(&mut self) -> Result<RegisterSnapshot, T::Error> {
+        self.driver.read_snapshot()
+    }
+
+    /// Read the registers into a [`StateDump`] for debug output.
+    pub fn state_dump(&mut self) -> Result<StateDump, T::Error> {
+        self.driver.state_dump()
+    }
+
+    /// Read the registers and write a human-readable report, see [`StateDump`].
+    pub fn dump_state(
+        &mut self,
+        out: &mut impl core::fmt::Write,
+    ) -> Result<(), DumpError<T::Error>> {
+        self.driver.dump_state(out)
+    }
+}
+
+/// Read-only view of an asynchronous driver.
+///
+/// Obtained from [`Tca9534Async::reader`], see [`Tca9534Reader`].
+///
+/// ```rust,compile_fail
+/// # async fn check<T: tca9534_driver_rs::AsyncTransport>(tca: &mut tca9534_driver_rs::Tca9534Async<T>) {
+/// let mut reader = tca.reader();
+/// reader.write_output_port(0xFF).await;
+/// # }
+/// ```
+#[cfg(feature = "async")]
+#[derive(Debug)]
+pub struct Tca9534AsyncReader<'a, T> {
+    driver: &'a mut Tca9534Async<T>,
+}
+
+#[cfg(feature = "async")]
+impl<'a, T> Tca9534AsyncReader<'a, T>
+where
+    T: AsyncTransport,
+{
+    pub(super) fn new(driver: &'a mut Tca9534Async<T>) -> Self {
+        Self { driver }
+    }
+
+    /// Get the I2C address of the device.
+    pub fn address(&self) -> u8 {
+        self.driver.address()
+    }
+
+    /// Read all input pins at once.
+    pub async fn read_input_port(&mut self) -> Result<u8, T::Error> {
+        self.driver.read_input_port().await
+    }
+
+    /// Read a specific input pin.
+    pub async fn read_pin_input(&mut self, pin: u8) -> Result<PinLevel, T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        self.driver.read_pin_input(pin).await
+    }
+
+    /// Read the Output Port register.
+    pub async fn read_output_port(&mut self) -> Result<u8, T::Error> {
+        self.driver.read_output_port().await
+    }
+
+    /// Read port configuration.
+    pub async fn read_port_config(&mut self) -> Result<u8, T::Error> {
+        self.driver.read_port_config().await
+    }
+
+    /// Read port polarity configuration.
+    pub async fn read_port_polarity(&mut self) -> Result<u8, T::Error> {
+        self.driver.read_port_polarity().await
+    }
+
+    /// Read all four registers.
+    pub async fn read_snapshot(&mut self) -> Result<RegisterSnapshot, T::Error> {
+        self.driver.read_snapshot().await
+    }
+
+    /// Read the registers into a [`StateDump`] for debug output.
+    pub async fn state_dump(&mut self) -> Result<StateDump, T::Error> {
+        self.driver.state_dump().await
+    }
+
+    /// Read the registers and write a human-readable report, see [`StateDump`].
+    pub async fn dump_state(
+        &mut self,
+        out: &mut impl core::fmt::Write,
+    ) -> Result<(), DumpError<T::Error>> {
+        self.driver.dump_state(out).await
+    }
+}
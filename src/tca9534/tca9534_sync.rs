@@ -1,39 +1,551 @@
+use super::batch::Batch;
+use super::reader::Tca9534Reader;
+use super::typed::{AddressMode, RuntimeAddress};
+use crate::diagnostics::*;
+#[cfg(feature = "energy")]
+use crate::energy::ByteCounts;
 use crate::error::*;
+use crate::events::InputChanges;
+#[cfg(feature = "embedded-hal")]
+use crate::initial_config::StartupOptions;
+use crate::initial_config::{AppliedReport, DegradedPolicy, InitMode, InitialConfig};
+use crate::pin_map::{NamedLevels, PinMap};
+use crate::port::Port;
 use crate::registers::*;
+use crate::seven_segment::SegMap;
 use crate::transport::SyncTransport;
+use core::marker::PhantomData;
+#[cfg(feature = "embedded-hal")]
+use embedded_hal::delay::DelayNs;
 
 /// TCA9534 synchronous driver structure.
-#[derive(Debug)]
-pub struct Tca9534<T> {
+///
+/// Usually named [`Tca9534Sync`](crate::Tca9534Sync) or
+/// [`Tca9534SyncAt`](crate::Tca9534SyncAt): `A` tells whether the address can change
+/// at runtime ([`RuntimeAddress`], the default) or is part of the type
+/// ([`FixedAddress`](crate::FixedAddress)).
+pub struct Tca9534<T, A = RuntimeAddress> {
     transport: T,
     address: u8,
+    /// Name of the device for logs, e.g. its role on the board.
+    label: Option<&'static str>,
+    /// Input port value captured at the end of `init()`.
+    init_input: u8,
+    /// Input port value at the end of the last `service_interrupt()`, or `init()`.
+    serviced_input: u8,
+    /// Extra Input Port reads done by `service_interrupt()` after a change.
+    interrupt_rereads: u8,
+    initialized: bool,
+    strict_init: bool,
+    /// Read back Polarity register writes of `set_pin_polarity`/`set_port_polarity`.
+    verify_polarity: bool,
+    /// Pins declared active low, one bit per pin.
+    active_low: u8,
+    /// Pins wired on the board; pin operations on the others fail with `PinReserved`.
+    usable_pins: u8,
+    /// What `read_input_port` returns when the bus fails.
+    degraded_policy: DegradedPolicy,
+    /// Last Input Port value read successfully by `read_input_port`.
+    last_good_input: Option<u8>,
+    /// Consecutive `read_input_port` calls served from `last_good_input`.
+    stale_reads: u8,
+    /// Last Output Port value written, or requested if the write failed.
+    desired_output: Option<u8>,
+    /// Register values the device is known to hold, from a completed write or a read,
+    /// indexed by register address.
+    written: [Option<u8>; 4],
+    /// Last Configuration value written, or requested if the write failed.
+    desired_config: Option<u8>,
+    /// Bus traffic per register.
+    #[cfg(feature = "energy")]
+    byte_counts: ByteCounts,
+    /// Register access of the last failed transfer.
+    #[cfg(feature = "error-context")]
+    failed_access: Option<FailedAccess>,
+    addressing: PhantomData<A>,
 }
 
-/// Synchronous implementation.
+/// Shows the label, the address in hex, the initialized flag and the cached direction,
+/// level and polarity of each pin; the transport, settings and bookkeeping are omitted.
+/// A fixed address is also shown in the type name, e.g. `Tca9534SyncAt<0x20>(..)`.
+impl<T, A: AddressMode> core::fmt::Debug for Tca9534<T, A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if let Some(address) = A::FIXED {
+            write!(f, "Tca9534SyncAt<{:#04x}>(", address)?;
+        }
+        f.debug_struct("Tca9534Sync")
+            .field("label", &self.label)
+            .field("address", &format_args!("{:#04x}", self.address))
+            .field("initialized", &self.initialized)
+            .field(
+                "pins",
+                &CachedPins {
+                    output: self.written[Register::OutputPort.addr() as usize],
+                    polarity: self.written[Register::Polarity.addr() as usize],
+                    config: self.written[Register::Config.addr() as usize],
+                },
+            )
+            .finish_non_exhaustive()?;
+        if A::FIXED.is_some() {
+            f.write_str(")")?;
+        }
+        Ok(())
+    }
+}
+
+/// Shows the label and address; use `dump_state_defmt` for the registers.
+#[cfg(feature = "defmt")]
+impl<T, A: AddressMode> defmt::Format for Tca9534<T, A> {
+    fn format(&self, fmt: defmt::Formatter) {
+        if let Some(address) = A::FIXED {
+            defmt::write!(fmt, "Tca9534SyncAt<{=u8:#04x}>(", address);
+        }
+        defmt::write!(
+            fmt,
+            "Tca9534Sync {{ label: {}, address: {=u8:#04x} }}",
+            self.label,
+            self.address
+        );
+        if A::FIXED.is_some() {
+            defmt::write!(fmt, ")");
+        }
+    }
+}
+
+/// Constructors and readdressing, for the driver with a runtime address.
 impl<T> Tca9534<T>
 where
     T: SyncTransport,
 {
     /// Create a new TCA9534 driver instance.
     pub fn new(transport: T, address: u8) -> Result<Self, T::Error> {
-        let mut ans = Self { transport, address };
+        let mut ans = Self::from_parts(transport, address);
         ans.init()?;
         Ok(ans)
     }
 
     /// Create a new TCA9534 driver instance with default address.
+    ///
+    /// The default address (0x20) is the one of the TCA9534/PCA9554; use
+    /// `with_family_default_address` for the A variants (0x38).
     pub fn with_default_address(transport: T) -> Result<Self, T::Error> {
-        let mut ans = Self {
-            transport,
-            address: addresses::ADDR_000,
-        };
+        let mut ans = Self::from_parts(transport, addresses::ADDR_000);
+        ans.init()?;
+        Ok(ans)
+    }
+
+    /// Create a new TCA9534 driver instance with the default address of a device family.
+    pub fn with_family_default_address(transport: T, family: Family) -> Result<Self, T::Error> {
+        Self::new(transport, family.default_address())
+    }
+
+    /// Create a new TCA9534 driver instance without writing to the device.
+    ///
+    /// The registers keep whatever they currently hold: the values of
+    /// [`defaults::POWER_ON_SNAPSHOT`] right after power-up (note the Output Port
+    /// is 0xFF there, not the 0x00 written by `new`), or the state left by a previous run.
+    pub fn new_without_init(transport: T, address: u8) -> Self {
+        Self::from_parts(transport, address)
+    }
+
+    /// Create a new TCA9534 driver instance keeping the power-on Output Port default (0xFF).
+    ///
+    /// Unlike `new`, which drives all outputs low, this matches the register values
+    /// listed in the datasheet, so register dumps compare cleanly against it.
+    pub fn new_with_por_output(transport: T, address: u8) -> Result<Self, T::Error> {
+        let mut ans = Self::from_parts(transport, address);
+        ans.init_with(config::POR_OUTPUT_DEFAULT, InitMode::Full)?;
+        Ok(ans)
+    }
+
+    /// Create a new TCA9534 driver instance, initializing it in the given mode.
+    ///
+    /// With [`InitMode::MinimalTraffic`], a device fresh out of reset only needs its
+    /// Output Port written, without the reads `new_minimal` does.
+    pub fn new_with_mode(transport: T, address: u8, mode: InitMode) -> Result<Self, T::Error> {
+        let mut ans = Self::from_parts(transport, address);
+        ans.init_with_mode(mode)?;
+        Ok(ans)
+    }
+
+    /// Create a new TCA9534 driver instance, writing only the registers that need it.
+    ///
+    /// Reaches the same state as `new`, but reads each register of [`INIT_SEQUENCE`]
+    /// first and skips the write if it already holds the init value. A device left
+    /// initialized by a previous run is not written at all, so its outputs are not
+    /// touched; a device fresh out of reset only needs its Output Port written.
+    pub fn new_minimal(transport: T, address: u8) -> Result<Self, T::Error> {
+        let mut ans = Self::from_parts(transport, address);
+        ans.init_minimal()?;
+        Ok(ans)
+    }
+
+    /// Create a new TCA9534 driver instance, retrying `init()` while the device powers up.
+    ///
+    /// See [`StartupOptions`] for the delays and attempts. If every attempt fails, the
+    /// transport is returned with the last error.
+    #[cfg(feature = "embedded-hal")]
+    pub fn new_with_startup(
+        transport: T,
+        address: u8,
+        options: StartupOptions,
+        delay: &mut impl DelayNs,
+    ) -> Result<Self, (T, T::Error)> {
+        delay.delay_us(options.settle_us);
+        let mut ans = Self::from_parts(transport, address);
+        let mut attempt = 1;
+        loop {
+            match ans.init() {
+                Ok(()) => return Ok(ans),
+                Err(error) if attempt >= options.init_attempts => {
+                    return Err((ans.transport, error));
+                }
+                Err(_) => {
+                    attempt += 1;
+                    delay.delay_us(options.attempt_delay_us);
+                }
+            }
+        }
+    }
+
+    /// Create a new TCA9534 driver instance with a custom startup configuration.
+    ///
+    /// Instead of the all-input init, writes the Output Port, Polarity and Config values
+    /// of `initial`, in that order, so pins turning into outputs start at their level.
+    pub fn new_with_config(
+        transport: T,
+        address: u8,
+        initial: &InitialConfig,
+    ) -> Result<Self, T::Error> {
+        let mut ans = Self::from_parts(transport, address);
+        ans.init_with_config(initial)?;
+        Ok(ans)
+    }
+
+    /// Create a new TCA9534 driver instance with a per-pin startup state.
+    ///
+    /// Instead of the all-input init, writes the Output Port, Polarity and Config values
+    /// of [`InitialConfig::from_pin_table`], in that order, so outputs start at their
+    /// table level.
+    pub fn new_with_pin_table(
+        transport: T,
+        address: u8,
+        table: &[(PinConfig, PinLevel); 8],
+    ) -> Result<Self, T::Error> {
+        Self::new_with_config(transport, address, &InitialConfig::from_pin_table(table))
+    }
+
+    /// Create a new TCA9534 driver instance for a board wiring only some of the pins.
+    ///
+    /// `usable` has one bit per wired pin. Pin operations on the other pins, and pin
+    /// masks containing them (`set_pins`, `write_output_masked`), fail with
+    /// [`Tca9534CoreError::PinReserved`] without a bus transaction. The test patterns
+    /// (`walking_*`, `self_test_pattern`) skip them; port-wide register writes are not
+    /// restricted. Initializes the device like `new`.
+    pub fn new_with_usable_pins(transport: T, address: u8, usable: u8) -> Result<Self, T::Error> {
+        let mut ans = Self::from_parts(transport, address);
+        ans.usable_pins = usable;
         ans.init()?;
         Ok(ans)
     }
 
+    /// Create a new TCA9534 driver instance from the A2, A1, A0 strap levels.
+    pub fn with_address_pins(transport: T, a2: bool, a1: bool, a0: bool) -> Result<Self, T::Error> {
+        Self::new(transport, addresses::for_straps(a2, a1, a0))
+    }
+
+    /// Create a new TCA9534 driver instance, validating the address against a device family.
+    pub fn new_with_family(transport: T, address: u8, family: Family) -> Result<Self, T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        if !family.contains(address) {
+            return Err(Tca9534CoreError::InvalidAddress.into());
+        }
+
+        Self::new(transport, address)
+    }
+
+    /// Probe the address windows of the given families and initialize the first device found.
+    ///
+    /// Families sharing an address window cannot be told apart on the bus, so the first
+    /// listed family whose window contains the responding address is reported.
+    /// The transport is handed back if no device responds.
+    pub fn auto_detect(mut transport: T, families: &[Family]) -> Result<(Self, Family), T> {
+        for &family in families {
+            match Self::auto_detect_in(transport, family.addresses()) {
+                Ok(ans) => return Ok((ans, family)),
+                Err(returned) => transport = returned,
+            }
+        }
+
+        Err(transport)
+    }
+
+    /// Probe the candidate addresses in order and initialize the first device found.
+    ///
+    /// The transport is handed back if no device responds.
+    pub fn auto_detect_in(
+        mut transport: T,
+        candidates: impl IntoIterator<Item = u8>,
+    ) -> Result<Self, T> {
+        for address in candidates {
+            if !Self::probe(&mut transport, address) {
+                continue;
+            }
+
+            let mut ans = Self::from_parts(transport, address);
+            match ans.init() {
+                Ok(()) => return Ok(ans),
+                Err(_) => transport = ans.transport,
+            }
+        }
+
+        Err(transport)
+    }
+
+    /// List the addresses that respond to a Config register read, in probing order.
+    ///
+    /// Any transfer error counts as no device. Responders beyond the capacity `N`
+    /// are not recorded.
+    #[cfg(feature = "heapless")]
+    pub fn scan<const N: usize>(
+        transport: &mut T,
+        addresses: impl IntoIterator<Item = u8>,
+    ) -> heapless::Vec<u8, N> {
+        let mut found = heapless::Vec::new();
+        for address in addresses {
+            if Self::probe(transport, address) && found.push(address).is_err() {
+                break;
+            }
+        }
+        found
+    }
+
+    /// Send the I2C general-call software reset (byte 0x06 to address 0x00).
+    ///
+    /// The TCA9534 datasheet does not list general-call support; this is for
+    /// footprint-compatible parts that document it. Every device on the bus that
+    /// implements the general-call reset is reset, not only expanders, and a bus
+    /// where no device acknowledges returns an error. Drivers of reset devices must
+    /// be initialized again.
+    pub fn general_call_reset(transport: &mut T) -> Result<(), T::Error> {
+        transport.write(addresses::GENERAL_CALL, &[GENERAL_CALL_RESET])
+    }
+
+    /// Read all four registers of a device before building a driver for it.
+    ///
+    /// For brown-out forensics: captures what the device was driving when the firmware
+    /// regained control, before `init()` overwrites it. Only register reads are issued,
+    /// so the device is left untouched.
+    pub fn capture_preinit_state(
+        transport: &mut T,
+        address: u8,
+    ) -> Result<RegisterSnapshot, T::Error> {
+        Ok(RegisterSnapshot {
+            input: Self::read_register_at(transport, address, Register::InputPort)?,
+            output: Self::read_register_at(transport, address, Register::OutputPort)?,
+            polarity: Self::read_register_at(transport, address, Register::Polarity)?,
+            config: Self::read_register_at(transport, address, Register::Config)?,
+        })
+    }
+
+    /// Read a register of the device at an address, without a driver.
+    fn read_register_at(transport: &mut T, address: u8, reg: Register) -> Result<u8, T::Error> {
+        let mut buffer = [0u8; 1];
+        transport.write_read(address, &[reg.addr()], &mut buffer)?;
+        Ok(buffer[0])
+    }
+
+    /// Check whether a device answers a Config register read at an address.
+    fn probe(transport: &mut T, address: u8) -> bool {
+        let mut buffer = [0u8; 1];
+        transport
+            .write_read(address, &[Register::Config.addr()], &mut buffer)
+            .is_ok()
+    }
+
+    /// Build the driver structure without touching the device.
+    fn from_parts(transport: T, address: u8) -> Self {
+        Self {
+            transport,
+            address,
+            label: None,
+            init_input: 0,
+            serviced_input: 0,
+            interrupt_rereads: 1,
+            initialized: false,
+            strict_init: false,
+            verify_polarity: false,
+            active_low: 0,
+            usable_pins: 0xFF,
+            degraded_policy: DegradedPolicy::Propagate,
+            last_good_input: None,
+            stale_reads: 0,
+            desired_output: None,
+            written: [None; 4],
+            desired_config: None,
+            #[cfg(feature = "energy")]
+            byte_counts: ByteCounts::default(),
+            #[cfg(feature = "error-context")]
+            failed_access: None,
+            addressing: PhantomData,
+        }
+    }
+
     /// Set I2C address (useful for multiple devices).
-    pub fn set_address(&mut self, address: u8) {
+    ///
+    /// Fails with [`Tca9534CoreError::InvalidAddress`] unless the address lies in the
+    /// window of one of the [`Family::ALL`] parts. Everything the driver knew about the
+    /// previous device is forgotten: the desired register values, the declared pin
+    /// active levels, the `init()` input capture and the initialized flag. No bus
+    /// transaction is issued; see [`switch_device`](Self::switch_device) to also read
+    /// the new device's state.
+    pub fn set_address(&mut self, address: u8) -> Result<(), Tca9534CoreError> {
+        if !Family::ALL.iter().any(|family| family.contains(address)) {
+            return Err(Tca9534CoreError::InvalidAddress);
+        }
         self.address = address;
+        self.init_input = 0;
+        self.serviced_input = 0;
+        self.initialized = false;
+        self.active_low = 0;
+        self.last_good_input = None;
+        self.stale_reads = 0;
+        self.desired_output = None;
+        self.written = [None; 4];
+        self.desired_config = None;
+        Ok(())
+    }
+
+    /// Set I2C address, builder style.
+    ///
+    /// Equivalent to `set_address`; no bus transaction is issued. On an invalid address
+    /// the driver is handed back unchanged with the error, so the transport is not lost.
+    pub fn with_address(mut self, address: u8) -> Result<Self, (Self, Tca9534CoreError)> {
+        match self.set_address(address) {
+            Ok(()) => Ok(self),
+            Err(error) => Err((self, error)),
+        }
+    }
+
+    /// Switch to the device at another address and read its registers.
+    ///
+    /// Like `set_address`, then [`resync`](Self::resync) takes the new device's Output
+    /// Port and Config values as the desired ones, so `reassert_*` and read-modify-write
+    /// operations start from its actual state. A device that does not respond fails
+    /// the read; the driver stays switched to the new address.
+    pub fn switch_device(&mut self, address: u8) -> Result<(), T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        self.set_address(address)?;
+        self.resync()?;
+        Ok(())
+    }
+}
+
+/// Synchronous implementation, for either [`AddressMode`].
+impl<T, A> Tca9534<T, A>
+where
+    T: SyncTransport,
+    A: AddressMode,
+{
+    /// Move the driver state into a driver of another address mode.
+    pub(super) fn into_mode<B>(self) -> Tca9534<T, B> {
+        Tca9534 {
+            transport: self.transport,
+            address: self.address,
+            label: self.label,
+            init_input: self.init_input,
+            serviced_input: self.serviced_input,
+            interrupt_rereads: self.interrupt_rereads,
+            initialized: self.initialized,
+            strict_init: self.strict_init,
+            verify_polarity: self.verify_polarity,
+            active_low: self.active_low,
+            usable_pins: self.usable_pins,
+            degraded_policy: self.degraded_policy,
+            last_good_input: self.last_good_input,
+            stale_reads: self.stale_reads,
+            desired_output: self.desired_output,
+            written: self.written,
+            desired_config: self.desired_config,
+            #[cfg(feature = "energy")]
+            byte_counts: self.byte_counts,
+            #[cfg(feature = "error-context")]
+            failed_access: self.failed_access,
+            addressing: PhantomData,
+        }
+    }
+
+    /// Name the device for logs, e.g. `"FRONT_PANEL"` when several expanders share a bus.
+    ///
+    /// The label is shown in the `Debug` and `defmt` output of the driver. It is kept
+    /// by `set_address` and `switch_device`.
+    pub fn set_label(&mut self, label: &'static str) {
+        self.label = Some(label);
+    }
+
+    /// Name the device for logs, builder style.
+    pub fn with_label(mut self, label: &'static str) -> Self {
+        self.set_label(label);
+        self
+    }
+
+    /// Get the label set with `set_label`.
+    pub fn label(&self) -> Option<&'static str> {
+        self.label
+    }
+
+    /// Get mutable access to the transport, e.g. to reach other devices on the bus.
+    ///
+    /// Writes to the expander through it are not seen by the driver; see
+    /// [`cached_output`](Self::cached_output) for keeping the cached values valid.
+    pub fn transport_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    /// Get the Output Port value the device is known to hold, without bus traffic.
+    ///
+    /// The cached values of `cached_output`, `cached_config` and `cached_polarity` come
+    /// from the last completed write of the register through the driver, from `init()`,
+    /// from `resync()`/`switch_device()`, or from an `assume_*` call. They are `None`
+    /// before any of these, after `set_address`, and after a failed write of the
+    /// register (which may or may not have reached the device). Changes the driver
+    /// cannot see, such as writes through [`transport_mut`](Self::transport_mut), by
+    /// another bus master, or a device reset, leave a stale value: call `resync()` or
+    /// the matching `assume_*` afterwards.
+    pub fn cached_output(&self) -> Option<u8> {
+        self.written[Register::OutputPort.addr() as usize]
+    }
+
+    /// Get the Config value the device is known to hold; see `cached_output`.
+    pub fn cached_config(&self) -> Option<u8> {
+        self.written[Register::Config.addr() as usize]
+    }
+
+    /// Get the Polarity value the device is known to hold; see `cached_output`.
+    pub fn cached_polarity(&self) -> Option<u8> {
+        self.written[Register::Polarity.addr() as usize]
+    }
+
+    /// Tell the driver the device holds `value` in the Output Port, without bus traffic.
+    ///
+    /// For writes made outside the driver. The value also becomes the desired one
+    /// rewritten by `reassert_outputs`.
+    pub fn assume_output(&mut self, value: u8) {
+        self.assume_written(Register::OutputPort, value);
+    }
+
+    /// Tell the driver the device holds `value` in the Config register; see `assume_output`.
+    pub fn assume_config(&mut self, value: u8) {
+        self.assume_written(Register::Config, value);
+    }
+
+    /// Tell the driver the device holds `value` in the Polarity register; see `assume_output`.
+    pub fn assume_polarity(&mut self, value: u8) {
+        self.assume_written(Register::Polarity, value);
     }
 
     /// Get current I2C address.
@@ -41,36 +553,682 @@ where
         self.address
     }
 
+    /// Enable or disable strict initialization checks (disabled by default).
+    ///
+    /// In strict mode, pin operations fail with [`Tca9534CoreError::NotInitialized`]
+    /// until `init()` or `ensure_initialized()` has run, which catches drivers created
+    /// with `new_without_init` and never initialized. Raw register and port accesses
+    /// are not checked.
+    pub fn set_strict_init(&mut self, strict: bool) {
+        self.strict_init = strict;
+    }
+
+    /// Enable or disable read-back of polarity writes (disabled by default).
+    ///
+    /// When enabled, `set_pin_polarity` reads the Polarity register after writing it
+    /// and fails with [`Tca9534CoreError::PolarityMismatch`] if the device did not take
+    /// the value, catching a flaky device that silently ignores writes. Costs one extra
+    /// read per call. For port-wide writes use `set_port_polarity_verified`.
+    pub fn set_polarity_verification(&mut self, verify: bool) {
+        self.verify_polarity = verify;
+    }
+
+    /// Check whether `init()` has completed on this driver instance.
+    pub fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
+    /// Initialize the device unless it already has been.
+    pub fn ensure_initialized(&mut self) -> Result<(), T::Error> {
+        if !self.initialized {
+            self.init()?;
+        }
+        Ok(())
+    }
+
     /// Initialize the device with default settings.
-    fn init(&mut self) -> Result<(), T::Error> {
-        // Set all pins as inputs (default state)
-        self.write_register(Register::Config, 0xFF)?;
+    ///
+    /// All outputs are set low, not to the datasheet power-on value
+    /// ([`config::POR_OUTPUT_DEFAULT`]).
+    pub fn init(&mut self) -> Result<(), T::Error> {
+        self.init_with_mode(InitMode::Full)
+    }
+
+    /// Initialize the device like `init()`, in the given mode.
+    ///
+    /// [`InitMode::MinimalTraffic`] skips the Config and Polarity writes, whose init
+    /// values match the power-on defaults, and takes those defaults as the desired values.
+    pub fn init_with_mode(&mut self, mode: InitMode) -> Result<(), T::Error> {
+        self.init_with(config::ALL_OUTPUTS_LOW, mode)
+    }
+
+    /// Initialize the device with the given Output Port value.
+    ///
+    /// Writes [`INIT_SEQUENCE`], with `output` in place of its Output Port value.
+    fn init_with(&mut self, output: u8, mode: InitMode) -> Result<(), T::Error> {
+        for &(reg, value) in INIT_SEQUENCE {
+            let value = if reg == Register::OutputPort {
+                output
+            } else {
+                value
+            };
+            self.write_init_register(reg, value, mode)?;
+        }
+
+        // Capture the input state for `inputs_changed_since_init()`
+        self.init_input = self.read_register(Register::InputPort)?;
+        self.serviced_input = self.init_input;
+        self.initialized = true;
+
+        Ok(())
+    }
+
+    /// Initialize the device with an [`InitialConfig`] in place of the init values.
+    fn init_with_config(&mut self, initial: &InitialConfig) -> Result<(), T::Error> {
+        self.apply_initial_config(initial)?;
+
+        self.init_input = self.read_register(Register::InputPort)?;
+        self.serviced_input = self.init_input;
+        self.initialized = true;
+
+        Ok(())
+    }
+
+    /// Initialize the device like `init()`, skipping writes of registers already at their init value.
+    fn init_minimal(&mut self) -> Result<(), T::Error> {
+        for &(reg, value) in INIT_SEQUENCE {
+            if self.read_register(reg)? == value {
+                self.assume_written(reg, value);
+            } else {
+                self.write_register(reg, value)?;
+            }
+        }
+
+        self.init_input = self.read_register(Register::InputPort)?;
+        self.serviced_input = self.init_input;
+        self.initialized = true;
+
+        Ok(())
+    }
+
+    /// Write an [`InitialConfig`] in glitch-free order (Output, Polarity, Config).
+    pub fn apply_initial_config(&mut self, initial: &InitialConfig) -> Result<(), T::Error> {
+        self.apply_initial_config_with_mode(initial, InitMode::Full)
+    }
+
+    /// Write an [`InitialConfig`] like `apply_initial_config`, in the given mode.
+    ///
+    /// [`InitMode::MinimalTraffic`] compares each register against its power-on default,
+    /// so only the registers the configuration changes from reset are written.
+    pub fn apply_initial_config_with_mode(
+        &mut self,
+        initial: &InitialConfig,
+        mode: InitMode,
+    ) -> Result<(), T::Error> {
+        self.write_init_register(Register::OutputPort, initial.output(), mode)?;
+        self.write_init_register(Register::Polarity, initial.polarity(), mode)?;
+        self.write_init_register(Register::Config, initial.config(), mode)
+    }
+
+    /// Apply a complete configuration, writing only the registers that change.
+    ///
+    /// Each register is compared with the value the device is known to hold (from
+    /// a previous write, `init()` or `resync()`); registers with no known value are
+    /// written. Writes go in glitch-free order (Output, Polarity, Config), so a pin
+    /// turning into an output already drives its new level. On error the registers
+    /// before the failing one have been written.
+    pub fn apply_config(&mut self, cfg: &InitialConfig) -> Result<AppliedReport, T::Error> {
+        Ok(AppliedReport {
+            output: self.write_if_changed(Register::OutputPort, cfg.output())?,
+            polarity: self.write_if_changed(Register::Polarity, cfg.polarity())?,
+            config: self.write_if_changed(Register::Config, cfg.config())?,
+        })
+    }
 
-        // Set all outputs to low (when configured as outputs)
-        self.write_register(Register::OutputPort, 0x00)?;
+    /// Write a register unless the device is known to hold `value`; returns whether it was written.
+    fn write_if_changed(&mut self, reg: Register, value: u8) -> Result<bool, T::Error> {
+        if self.written[reg.addr() as usize] == Some(value) {
+            return Ok(false);
+        }
+        self.write_register(reg, value)?;
+        Ok(true)
+    }
+
+    /// Write a register as part of an init, unless the mode skips it.
+    ///
+    /// A skipped register is assumed to hold `value` and taken as the desired value.
+    fn write_init_register(
+        &mut self,
+        reg: Register,
+        value: u8,
+        mode: InitMode,
+    ) -> Result<(), T::Error> {
+        if !mode.skips(reg, value) {
+            return self.write_register(reg, value);
+        }
+        self.assume_written(reg, value);
+        Ok(())
+    }
+
+    /// Take a register as holding `value` without writing it, e.g. after reading it back.
+    fn assume_written(&mut self, reg: Register, value: u8) {
+        match reg {
+            Register::OutputPort => self.desired_output = Some(value),
+            Register::Config => self.desired_config = Some(value),
+            _ => {}
+        }
+        self.written[reg.addr() as usize] = Some(value);
+    }
+
+    /// Write the Config, Polarity and Output Port registers and read them back.
+    ///
+    /// The registers are written in glitch-free order (Output, Polarity, Config), then
+    /// each is read back; any difference fails with [`Tca9534CoreError::VerifyFailed`].
+    /// The device does not auto-increment its register pointer, so this takes three
+    /// writes and three reads.
+    pub fn program(&mut self, config: u8, polarity: u8, output: u8) -> Result<(), T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        let expected = [
+            (Register::OutputPort, output),
+            (Register::Polarity, polarity),
+            (Register::Config, config),
+        ];
+        for (reg, value) in expected {
+            self.write_register(reg, value)?;
+        }
+        for (reg, value) in expected {
+            if self.read_register(reg)? != value {
+                return Err(Tca9534CoreError::VerifyFailed.into());
+            }
+        }
+        Ok(())
+    }
 
-        // Set all polarities to normal (non-inverted)
-        self.write_register(Register::Polarity, 0x00)?;
+    /// Validate a pin number and that the board wires the pin and, in strict mode,
+    /// that the device is initialized.
+    pub(super) fn check_pin(&self, pin: u8) -> Result<(), Tca9534CoreError> {
+        if pin > 7 {
+            return Err(Tca9534CoreError::InvalidPin);
+        }
+        if self.usable_pins & (1 << pin) == 0 {
+            return Err(Tca9534CoreError::PinReserved);
+        }
+        if self.strict_init && !self.initialized {
+            return Err(Tca9534CoreError::NotInitialized);
+        }
+        Ok(())
+    }
 
+    /// Validate a pin mask like `check_pin`: every pin in it must be wired on the board.
+    fn check_mask(&self, mask: Port) -> Result<(), Tca9534CoreError> {
+        if mask.bits() & !self.usable_pins != 0 {
+            return Err(Tca9534CoreError::PinReserved);
+        }
+        if self.strict_init && !self.initialized {
+            return Err(Tca9534CoreError::NotInitialized);
+        }
         Ok(())
     }
 
     /// Read a register.
     pub fn read_register(&mut self, reg: Register) -> Result<u8, T::Error> {
         let mut buffer = [0u8; 1];
-        self.transport
-            .write_read(self.address, &[reg.addr()], &mut buffer)?;
+        #[cfg(feature = "energy")]
+        self.byte_counts.record(reg, 1, 1);
+        let result = self
+            .transport
+            .write_read(self.address, &[reg.addr()], &mut buffer);
+        self.track(result, reg, Direction::Read)?;
         Ok(buffer[0])
     }
 
+    /// Poll a register, sending the command byte only for the first read.
+    ///
+    /// The device keeps its register pointer between transactions, so subsequent
+    /// reads are bare reads. `until` is called with each value read and polling stops
+    /// when it returns `true`; the last value read is returned. If `until` still
+    /// returns `false` after `max_polls` reads (the first one included), fails with
+    /// [`Tca9534CoreError::Timeout`]; a `max_polls` of zero fails with
+    /// [`Tca9534CoreError::InvalidArgument`]. Another master accessing the device
+    /// during the poll would move the pointer.
+    pub fn poll_register_fast(
+        &mut self,
+        reg: Register,
+        max_polls: u32,
+        mut until: impl FnMut(u8) -> bool,
+    ) -> Result<u8, T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        if max_polls == 0 {
+            return Err(Tca9534CoreError::InvalidArgument.into());
+        }
+        let mut value = self.read_register(reg)?;
+        for _ in 1..max_polls {
+            if until(value) {
+                return Ok(value);
+            }
+            let mut buffer = [0u8; 1];
+            #[cfg(feature = "energy")]
+            self.byte_counts.record(reg, 1, 0);
+            let result = self.transport.read(self.address, &mut buffer);
+            self.track(result, reg, Direction::Read)?;
+            value = buffer[0];
+        }
+        if until(value) {
+            Ok(value)
+        } else {
+            Err(Tca9534CoreError::Timeout.into())
+        }
+    }
+
     /// Write to a register.
     pub fn write_register(&mut self, reg: Register, value: u8) -> Result<(), T::Error> {
-        self.transport.write(self.address, &[reg.addr(), value])
+        match reg {
+            Register::OutputPort => self.desired_output = Some(value),
+            Register::Config => self.desired_config = Some(value),
+            _ => {}
+        }
+        // A failed write may or may not have reached the device
+        self.written[reg.addr() as usize] = None;
+        #[cfg(feature = "energy")]
+        self.byte_counts.record(reg, 0, 2);
+        let result = self.transport.write(self.address, &[reg.addr(), value]);
+        self.track(result, reg, Direction::Write)?;
+        self.written[reg.addr() as usize] = Some(value);
+        Ok(())
+    }
+
+    /// Write the Output Port with a single bus transaction and no bookkeeping.
+    ///
+    /// For emergency paths such as a high-priority stop handler: no pin or init checks,
+    /// and the cached and desired register values are not updated. Call
+    /// [`mark_caches_stale`](Self::mark_caches_stale) from normal context afterwards.
+    pub fn force_write_output_port(&mut self, value: u8) -> Result<(), T::Error> {
+        self.transport
+            .write(self.address, &[Register::OutputPort.addr(), value])
+    }
+
+    /// Get the register access of the most recent failed bus transfer.
+    ///
+    /// Tells e.g. whether the read or the write of a failing `set_pin_output` failed;
+    /// the error itself stays `I2c(E)`. Only recorded with the `error-context` feature,
+    /// otherwise always `None`. Recorded for every driver transfer except
+    /// `force_write_output_port`; successful transfers leave it unchanged.
+    pub fn last_failed_access(&self) -> Option<FailedAccess> {
+        #[cfg(feature = "error-context")]
+        {
+            self.failed_access
+        }
+        #[cfg(not(feature = "error-context"))]
+        {
+            None
+        }
+    }
+
+    /// Record the register access of a failed transfer (`error-context` feature).
+    fn track<R>(
+        &mut self,
+        result: Result<R, T::Error>,
+        register: Register,
+        direction: Direction,
+    ) -> Result<R, T::Error> {
+        #[cfg(feature = "error-context")]
+        if result.is_err() {
+            self.failed_access = Some(FailedAccess {
+                register,
+                direction,
+            });
+        }
+        #[cfg(not(feature = "error-context"))]
+        let _ = (register, direction);
+        result
+    }
+
+    /// Get the bus traffic since construction or the last `reset_byte_counts`.
+    ///
+    /// Counts the transfers of this driver to its device; `force_write_output_port`,
+    /// `general_call_reset`, probing and direct use of `transport_mut` are not included.
+    #[cfg(feature = "energy")]
+    pub fn byte_counts(&self) -> ByteCounts {
+        self.byte_counts
+    }
+
+    /// Zero the bus traffic counters.
+    #[cfg(feature = "energy")]
+    pub fn reset_byte_counts(&mut self) {
+        self.byte_counts = ByteCounts::default();
+    }
+
+    /// Forget the cached and desired register values, e.g. after `force_write_output_port`.
+    ///
+    /// `cached_*` then return `None`, `apply_config` and `Batch::flush_output` write
+    /// unconditionally, and `reassert_*` fail until `resync()` reads the device, so the
+    /// values from before the emergency write are never restored by accident.
+    pub fn mark_caches_stale(&mut self) {
+        self.written = [None; 4];
+        self.desired_output = None;
+        self.desired_config = None;
+    }
+
+    /// Read a register, apply `f` to its value and write the result back.
+    ///
+    /// Returns the value written. The Input Port register is read-only and fails with
+    /// [`Tca9534CoreError::InvalidArgument`] without a bus transaction.
+    pub fn modify_register(
+        &mut self,
+        reg: Register,
+        f: impl FnOnce(u8) -> u8,
+    ) -> Result<u8, T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        if reg == Register::InputPort {
+            return Err(Tca9534CoreError::InvalidArgument.into());
+        }
+
+        let value = f(self.read_register(reg)?);
+        self.write_register(reg, value)?;
+        Ok(value)
+    }
+
+    /// Rewrite the desired Output Port value, even if the device should already hold it.
+    ///
+    /// Call periodically on noisy boards to recover from corrupted registers. The
+    /// desired value is the last one written through the driver; fails with
+    /// [`Tca9534CoreError::NothingToReassert`] if none is known.
+    pub fn reassert_outputs(&mut self) -> Result<(), T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        let value = self
+            .desired_output
+            .ok_or(Tca9534CoreError::NothingToReassert)?;
+        self.write_register(Register::OutputPort, value)
+    }
+
+    /// Rewrite the desired Configuration value; see `reassert_outputs`.
+    pub fn reassert_config(&mut self) -> Result<(), T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        let value = self
+            .desired_config
+            .ok_or(Tca9534CoreError::NothingToReassert)?;
+        self.write_register(Register::Config, value)
+    }
+
+    /// Re-read the device registers and take them as the desired values.
+    ///
+    /// Use after any write outside the driver, so that `reassert_*` rewrites what
+    /// the device actually holds.
+    pub fn resync(&mut self) -> Result<RegisterSnapshot, T::Error> {
+        let snapshot = self.read_snapshot()?;
+        for reg in [Register::OutputPort, Register::Polarity, Register::Config] {
+            self.assume_written(reg, snapshot.get(reg));
+        }
+        Ok(snapshot)
+    }
+
+    /// Read all four registers.
+    pub fn read_snapshot(&mut self) -> Result<RegisterSnapshot, T::Error> {
+        Ok(RegisterSnapshot {
+            input: self.read_register(Register::InputPort)?,
+            output: self.read_register(Register::OutputPort)?,
+            polarity: self.read_register(Register::Polarity)?,
+            config: self.read_register(Register::Config)?,
+        })
+    }
+
+    /// Read the registers into a [`StateDump`] for debug output.
+    pub fn state_dump(&mut self) -> Result<StateDump, T::Error> {
+        Ok(StateDump {
+            address: self.address,
+            registers: self.read_snapshot()?,
+        })
+    }
+
+    /// Compare the device registers against an expected configuration, e.g. a board profile.
+    ///
+    /// Reads a [`RegisterSnapshot`] (all four registers) and writes nothing; see
+    /// [`ConfigDiff::new`] for the differing bits. Use `apply_config` to bring the device
+    /// in line.
+    pub fn check_against(&mut self, expected: &InitialConfig) -> Result<ConfigDiff, T::Error> {
+        let snapshot = self.read_snapshot()?;
+        Ok(ConfigDiff::new(expected, &snapshot))
+    }
+
+    /// Get the mask of output pins whose actual level differs from the commanded one.
+    ///
+    /// A set bit suggests a short or an overload pulling the pin. Reads all four
+    /// registers (the device has no multi-register read) and compensates the Input Port
+    /// for the Polarity register; input pins are never flagged. See [`StateDump::mismatches`].
+    pub fn detect_output_faults(&mut self) -> Result<u8, T::Error> {
+        Ok(self.state_dump()?.mismatches())
+    }
+
+    /// Read the registers into a [`DeviceDescription`] bring-up report.
+    ///
+    /// Besides the registers, it records the family matching the address, the label, the
+    /// initialized flag and the cached register values as they were before the read;
+    /// the read does not update them.
+    pub fn describe(&mut self) -> Result<DeviceDescription, T::Error> {
+        let (cached_output, cached_polarity, cached_config) = (
+            self.cached_output(),
+            self.cached_polarity(),
+            self.cached_config(),
+        );
+        Ok(DeviceDescription {
+            state: self.state_dump()?,
+            family: Family::ALL
+                .iter()
+                .copied()
+                .find(|family| family.contains(self.address)),
+            label: self.label,
+            initialized: self.initialized,
+            cached_output,
+            cached_polarity,
+            cached_config,
+        })
+    }
+
+    /// Read the registers and write a human-readable report, see [`StateDump`].
+    ///
+    /// Nothing is allocated; the report is written piecewise to `out`.
+    pub fn dump_state(
+        &mut self,
+        out: &mut impl core::fmt::Write,
+    ) -> Result<(), DumpError<T::Error>> {
+        let dump = self.state_dump().map_err(DumpError::Bus)?;
+        write!(out, "{}", dump).map_err(|_| DumpError::Format)
+    }
+
+    /// Read the registers and encode them into `buf`, see [`StateDump::encode`].
+    ///
+    /// Returns the length written. A buffer shorter than [`StateDump::ENCODED_LEN`]
+    /// fails with [`Tca9534CoreError::InvalidArgument`] before any bus transaction.
+    pub fn format_state_into(&mut self, buf: &mut [u8]) -> Result<usize, T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        if buf.len() < StateDump::ENCODED_LEN {
+            return Err(Tca9534CoreError::InvalidArgument.into());
+        }
+        let dump = self.state_dump()?;
+        dump.encode(buf)
+            .ok_or_else(|| Tca9534CoreError::InvalidArgument.into())
+    }
+
+    /// Read the registers and log them with defmt at info level.
+    #[cfg(feature = "defmt")]
+    pub fn dump_state_defmt(&mut self) -> Result<(), T::Error> {
+        let dump = self.state_dump()?;
+        defmt::info!("{}", dump);
+        Ok(())
+    }
+
+    /// Check whether the device looks like it went through a power-on reset.
+    ///
+    /// This is a heuristic: it reports `true` when the writable registers hold the
+    /// values of [`defaults::POWER_ON_SNAPSHOT`], which a configuration may also match.
+    /// The Output Port default (0xFF) differs from the value written by `init()` (0x00),
+    /// so a freshly initialized device is not reported as reset.
+    pub fn detect_device_reset(&mut self) -> Result<bool, T::Error> {
+        let snapshot = self.read_snapshot()?;
+        Ok(snapshot.writable_eq(&defaults::POWER_ON_SNAPSHOT))
+    }
+
+    /// Start a batch of updates that are written to the device in one flush.
+    ///
+    /// See [`Batch`] for the flush order and the behavior on drop.
+    pub fn begin_batch(&mut self) -> Batch<'_, T, A> {
+        Batch::new(self)
+    }
+
+    /// Borrow the driver as a read-only view.
+    ///
+    /// See [`Tca9534Reader`] for the methods it exposes.
+    pub fn reader(&mut self) -> Tca9534Reader<'_, T, A> {
+        Tca9534Reader::new(self)
     }
 
     /// Read all input pins at once.
+    ///
+    /// Under [`DegradedPolicy::ServeCached`], a failed read may return the last value
+    /// read successfully; see [`last_read_was_stale`](Self::last_read_was_stale). The
+    /// reads built on this one (`read_pin_input`, `read_all_pins`, ...) behave the same.
     pub fn read_input_port(&mut self) -> Result<u8, T::Error> {
-        self.read_register(Register::InputPort)
+        match self.read_register(Register::InputPort) {
+            Ok(value) => {
+                self.last_good_input = Some(value);
+                self.stale_reads = 0;
+                Ok(value)
+            }
+            Err(error) => match (self.degraded_policy, self.last_good_input) {
+                (DegradedPolicy::ServeCached { max_age }, Some(value))
+                    if self.stale_reads < max_age =>
+                {
+                    self.stale_reads += 1;
+                    Ok(value)
+                }
+                _ => Err(error),
+            },
+        }
+    }
+
+    /// Set what Input Port reads return when the bus fails (default [`DegradedPolicy::Propagate`]).
+    ///
+    /// Only affects `read_input_port` and the reads built on it. Writes, `init()`,
+    /// `service_interrupt` and the other register reads always report bus errors.
+    pub fn set_degraded_policy(&mut self, policy: DegradedPolicy) {
+        self.degraded_policy = policy;
+    }
+
+    /// Get the degraded-mode policy.
+    pub fn degraded_policy(&self) -> DegradedPolicy {
+        self.degraded_policy
+    }
+
+    /// Check whether the last value returned by `read_input_port` came from the cache.
+    ///
+    /// Stays set until a read succeeds, including when a later read returned an error.
+    pub fn last_read_was_stale(&self) -> bool {
+        self.stale_reads != 0
+    }
+
+    /// Read all input pins at once, flagging all-ones/all-zeros values as suspicious.
+    ///
+    /// See [`InputReading`]: the flag is a heuristic for an absent device or stuck bus.
+    pub fn read_input_port_checked(&mut self) -> Result<InputReading, T::Error> {
+        Ok(InputReading::from_raw(self.read_input_port()?))
+    }
+
+    /// Set how many extra reads `service_interrupt` may do after a change (default 1).
+    pub fn set_interrupt_rereads(&mut self, rereads: u8) {
+        self.interrupt_rereads = rereads;
+    }
+
+    /// Collect the input changes since the last call; call after each INT assertion.
+    ///
+    /// Reading the Input Port clears INT. A change landing while that read is in
+    /// progress is latched by the read but does not assert INT again, so waiting for
+    /// the next INT would leave it unreported until some unrelated change. After a
+    /// read that found a change, the port is therefore read again, up to the number
+    /// set by [`set_interrupt_rereads`](Self::set_interrupt_rereads), until two
+    /// consecutive reads agree. Every change seen along the way is reported.
+    ///
+    /// Changes are relative to the value at the end of the previous call, or of
+    /// `init()` for the first call.
+    pub fn service_interrupt(&mut self) -> Result<InputChanges, T::Error> {
+        let previous = self.serviced_input;
+        let mut current = self.read_register(Register::InputPort)?;
+        let mut changed = previous ^ current;
+        if changed != 0 {
+            for _ in 0..self.interrupt_rereads {
+                let value = self.read_register(Register::InputPort)?;
+                if value == current {
+                    break;
+                }
+                changed |= current ^ value;
+                current = value;
+            }
+        }
+        self.serviced_input = current;
+        Ok(InputChanges {
+            previous,
+            current,
+            changed,
+        })
+    }
+
+    /// Read the Input Port once it has stopped changing.
+    ///
+    /// Samples every `interval_us` microseconds until `required_consecutive` reads in a
+    /// row return the same value, which is returned. Fails with
+    /// [`StableReadError::NotStable`] if `max_samples` reads did not get there, e.g. a
+    /// switch being flipped while the board boots. `required_consecutive` must be in
+    /// `1..=max_samples`, otherwise [`Tca9534CoreError::InvalidArgument`] is returned
+    /// as a bus error.
+    #[cfg(feature = "embedded-hal")]
+    pub fn read_input_port_stable(
+        &mut self,
+        required_consecutive: u8,
+        max_samples: u8,
+        interval_us: u32,
+        delay: &mut impl DelayNs,
+    ) -> Result<u8, StableReadError<T::Error>>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        if required_consecutive == 0 || required_consecutive > max_samples {
+            return Err(StableReadError::Bus(
+                Tca9534CoreError::InvalidArgument.into(),
+            ));
+        }
+
+        let mut last = self
+            .read_register(Register::InputPort)
+            .map_err(StableReadError::Bus)?;
+        let mut consecutive = 1;
+        for _ in 1..max_samples {
+            if consecutive == required_consecutive {
+                break;
+            }
+            delay.delay_us(interval_us);
+            let value = self
+                .read_register(Register::InputPort)
+                .map_err(StableReadError::Bus)?;
+            consecutive = if value == last { consecutive + 1 } else { 1 };
+            last = value;
+        }
+
+        if consecutive == required_consecutive {
+            Ok(last)
+        } else {
+            Err(StableReadError::NotStable { last })
+        }
     }
 
     /// Read a specific input pin.
@@ -78,44 +1236,399 @@ where
     where
         T::Error: From<Tca9534CoreError>,
     {
-        if pin > 7 {
-            return Err(Tca9534CoreError::InvalidPin.into());
-        }
+        self.check_pin(pin)?;
 
         let port_value = self.read_input_port()?;
-        let pin_value = (port_value >> pin) & 0x01;
-        Ok(if pin_value == 0 {
-            PinLevel::Low
-        } else {
+        Ok(PinLevel::from_port(port_value, pin))
+    }
+
+    /// Read a specific input pin, keeping driver and transport errors apart.
+    ///
+    /// Unlike `read_pin_input`, this needs no `From<Tca9534CoreError>` bound: an invalid
+    /// pin is [`Tca9534Error::Core`] and a transport failure is [`Tca9534Error::I2c`]
+    /// wrapping `T::Error`, so the two can be matched without knowing the transport.
+    pub fn try_read_pin_input(&mut self, pin: u8) -> Result<PinLevel, Tca9534Error<T::Error>> {
+        self.check_pin(pin)?;
+
+        let port_value = self.read_input_port().map_err(Tca9534Error::I2c)?;
+        Ok(PinLevel::from_port(port_value, pin))
+    }
+
+    /// Declare the electrical level at which a pin's signal is active (high by default).
+    ///
+    /// Only affects `read_pin_logical` and `read_pin_active`; no bus transaction is issued.
+    pub fn declare_pin_active_level(&mut self, pin: u8, level: PinLevel) -> Result<(), T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        self.check_pin(pin)?;
+
+        match level {
+            PinLevel::Low => self.active_low |= 1 << pin,
+            PinLevel::High => self.active_low &= !(1 << pin),
+        }
+        Ok(())
+    }
+
+    /// Get the pins wired on the board, one bit per pin (0xFF unless set at construction).
+    pub fn usable_pins(&self) -> u8 {
+        self.usable_pins
+    }
+
+    /// Get the declared active level of a pin.
+    pub fn pin_active_level(&self, pin: u8) -> Result<PinLevel, T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        self.check_pin(pin)?;
+        Ok(PinLevel::from_port(!self.active_low, pin))
+    }
+
+    /// Read the logical state of an input pin.
+    ///
+    /// The Input Port reflects the Polarity register, so it is undone first to get the
+    /// electrical level, which is then compared against the declared active level.
+    pub fn read_pin_logical(&mut self, pin: u8) -> Result<LogicalLevel, T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        self.check_pin(pin)?;
+
+        let input = self.read_input_port()?;
+        let polarity = self.read_port_polarity()?;
+        let level = PinLevel::from_port(input ^ polarity, pin);
+        Ok(LogicalLevel::from_level(level, self.pin_active_level(pin)?))
+    }
+
+    /// Check whether an input pin's signal is active; see `read_pin_logical`.
+    pub fn read_pin_active(&mut self, pin: u8) -> Result<bool, T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        Ok(self.read_pin_logical(pin)?.is_active())
+    }
+
+    /// Wait until an input pin reads `level`, polling every `poll_us` microseconds.
+    ///
+    /// The Input Port reflects the Polarity register, like [`read_pin_input`](Self::read_pin_input).
+    /// The pin is read once more when `timeout_us` has elapsed (counting delays only,
+    /// not bus time); if it still differs, fails with [`Tca9534CoreError::Timeout`].
+    /// A `poll_us` of zero fails with [`Tca9534CoreError::InvalidArgument`].
+    #[cfg(feature = "embedded-hal")]
+    pub fn wait_for_level(
+        &mut self,
+        pin: u8,
+        level: PinLevel,
+        delay: &mut impl DelayNs,
+        poll_us: u32,
+        timeout_us: u32,
+    ) -> Result<(), T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        self.check_pin(pin)?;
+        if poll_us == 0 {
+            return Err(Tca9534CoreError::InvalidArgument.into());
+        }
+
+        let mut elapsed_us = 0u32;
+        loop {
+            if self.read_pin_input(pin)? == level {
+                return Ok(());
+            }
+            if elapsed_us >= timeout_us {
+                return Err(Tca9534CoreError::Timeout.into());
+            }
+            let step_us = poll_us.min(timeout_us - elapsed_us);
+            delay.delay_us(step_us);
+            elapsed_us += step_us;
+        }
+    }
+
+    /// Wait until an input pin reads high, see [`wait_for_level`](Self::wait_for_level).
+    #[cfg(feature = "embedded-hal")]
+    pub fn wait_high(
+        &mut self,
+        pin: u8,
+        delay: &mut impl DelayNs,
+        poll_us: u32,
+        timeout_us: u32,
+    ) -> Result<(), T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        self.wait_for_level(pin, PinLevel::High, delay, poll_us, timeout_us)
+    }
+
+    /// Wait until an input pin reads low, see [`wait_for_level`](Self::wait_for_level).
+    #[cfg(feature = "embedded-hal")]
+    pub fn wait_low(
+        &mut self,
+        pin: u8,
+        delay: &mut impl DelayNs,
+        poll_us: u32,
+        timeout_us: u32,
+    ) -> Result<(), T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        self.wait_for_level(pin, PinLevel::Low, delay, poll_us, timeout_us)
+    }
+
+    /// Read an input pin `samples` times and return the majority level.
+    ///
+    /// A simple debounce for noisy contacts, with reads `interval_us` microseconds
+    /// apart. `samples` must be odd so there are no ties; zero or an even count
+    /// fails with [`Tca9534CoreError::InvalidArgument`].
+    #[cfg(feature = "embedded-hal")]
+    pub fn read_pin_sampled(
+        &mut self,
+        pin: u8,
+        samples: u8,
+        delay: &mut impl DelayNs,
+        interval_us: u32,
+    ) -> Result<PinLevel, T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        self.check_pin(pin)?;
+        if samples & 1 == 0 {
+            return Err(Tca9534CoreError::InvalidArgument.into());
+        }
+
+        let mut high = 0;
+        for sample in 0..samples {
+            if sample > 0 {
+                delay.delay_us(interval_us);
+            }
+            if self.read_pin_input(pin)? == PinLevel::High {
+                high += 1;
+            }
+        }
+        Ok(if high > samples / 2 {
             PinLevel::High
+        } else {
+            PinLevel::Low
         })
     }
 
+    /// Read the level of all 8 pins at once, indexed by pin number.
+    pub fn read_all_pins(&mut self) -> Result<[PinLevel; 8], T::Error> {
+        let port_value = self.read_input_port()?;
+        Ok(core::array::from_fn(|pin| {
+            PinLevel::from_port(port_value, pin as u8)
+        }))
+    }
+
+    /// Read the levels of the pins named in `map`, with a single port read.
+    pub fn read_named<'a>(&mut self, map: &PinMap<'a>) -> Result<NamedLevels<'a>, T::Error> {
+        let port_value = self.read_input_port()?;
+        Ok(map.levels(port_value))
+    }
+
+    /// Check whether any input differs from the state captured during `init()`.
+    pub fn inputs_changed_since_init(&mut self) -> Result<bool, T::Error> {
+        Ok(self.read_input_port()? != self.init_input)
+    }
+
     /// Write all output pins at once.
     pub fn write_output_port(&mut self, value: u8) -> Result<(), T::Error> {
         self.write_register(Register::OutputPort, value)
     }
 
+    /// Write the output pins in `mask` to the levels in `value`, leaving the others unchanged.
+    ///
+    /// Fails with [`Tca9534CoreError::PinReserved`] without a bus transaction if `mask`
+    /// contains a pin the board does not wire.
+    pub fn write_output_masked(&mut self, mask: Port, value: Port) -> Result<(), T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        self.check_mask(mask)?;
+        let current = Port(self.read_output_port()?);
+        self.write_output_port(((current & !mask) | (value & mask)).bits())
+    }
+
+    /// Drive every output pin in `mask` to `level`, leaving the others unchanged.
+    ///
+    /// Takes one Output Port read and one write, however many pins are set; see
+    /// `write_output_masked` for reserved pins.
+    pub fn set_pins(&mut self, mask: Port, level: PinLevel) -> Result<(), T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        let value = match level {
+            PinLevel::High => mask,
+            PinLevel::Low => Port::NONE,
+        };
+        self.write_output_masked(mask, value)
+    }
+
+    /// Write the Output Port only if it currently holds `expected`.
+    ///
+    /// For buses where another master also writes the Output Port: on a mismatch the
+    /// value read is returned as `Err` so the caller can recompute `new` and retry.
+    /// This is not atomic; the other master can still write between the read and the
+    /// write, but the window is limited to one read and one write transaction.
+    pub fn compare_and_write_output(
+        &mut self,
+        expected: u8,
+        new: u8,
+    ) -> Result<Result<(), u8>, T::Error> {
+        let current = self.read_output_port()?;
+        if current != expected {
+            return Ok(Err(current));
+        }
+        self.write_output_port(new)?;
+        Ok(Ok(()))
+    }
+
+    /// Change the outputs to `target` one pin at a time, to limit inrush current.
+    ///
+    /// Reads the Output Port, then writes it once per changing pin, in `order`, waiting
+    /// `step_delay_us` microseconds between writes. A [`StaggerOrder::Custom`] order
+    /// must list every changing pin, or the call fails with
+    /// [`Tca9534CoreError::InvalidArgument`] before any write.
+    #[cfg(feature = "embedded-hal")]
+    pub fn write_output_staggered(
+        &mut self,
+        target: u8,
+        order: StaggerOrder<'_>,
+        step_delay_us: u32,
+        delay: &mut impl DelayNs,
+    ) -> Result<(), T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        self.stagger_output(target, order, step_delay_us, delay, false)
+    }
+
+    /// Like `write_output_staggered`, but pins turning off are cleared at once.
+    ///
+    /// The first write clears every pin that goes low; only the turn-ons are staggered.
+    #[cfg(feature = "embedded-hal")]
+    pub fn write_output_staggered_on(
+        &mut self,
+        target: u8,
+        order: StaggerOrder<'_>,
+        step_delay_us: u32,
+        delay: &mut impl DelayNs,
+    ) -> Result<(), T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        self.stagger_output(target, order, step_delay_us, delay, true)
+    }
+
+    /// Write the intermediate values of `write_output_staggered*`.
+    #[cfg(feature = "embedded-hal")]
+    fn stagger_output(
+        &mut self,
+        target: u8,
+        order: StaggerOrder<'_>,
+        step_delay_us: u32,
+        delay: &mut impl DelayNs,
+        offs_first: bool,
+    ) -> Result<(), T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        let listed = order.mask()?;
+        let mut value = self.read_output_port()?;
+        let turning_off = value & !target;
+        let mut pending = value ^ target;
+        if offs_first {
+            pending &= !turning_off;
+        }
+        if pending & !listed != 0 {
+            return Err(Tca9534CoreError::InvalidArgument.into());
+        }
+
+        let mut first = true;
+        if offs_first && turning_off != 0 {
+            value &= !turning_off;
+            self.write_output_port(value)?;
+            first = false;
+        }
+        for index in 0..8 {
+            let Some(pin) = order.nth(index) else {
+                break;
+            };
+            let bit = 1 << pin;
+            if pending & bit == 0 {
+                continue;
+            }
+            pending &= !bit;
+            if !first {
+                delay.delay_us(step_delay_us);
+            }
+            first = false;
+            value ^= bit;
+            self.write_output_port(value)?;
+        }
+        Ok(())
+    }
+
+    /// Display a hexadecimal digit (0-15) on a 7-segment display wired to the port.
+    ///
+    /// All 8 Output Port bits are written; pins not listed in `mapping` are driven low
+    /// (high for a common anode mapping).
+    pub fn write_seven_segment(
+        &mut self,
+        digit: u8,
+        dp: bool,
+        mapping: &SegMap,
+    ) -> Result<(), T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        let value = mapping
+            .encode(digit, dp)
+            .ok_or(Tca9534CoreError::InvalidArgument)?;
+        self.write_output_port(value)
+    }
+
     /// Read current output port register value.
     pub fn read_output_port(&mut self) -> Result<u8, T::Error> {
         self.read_register(Register::OutputPort)
     }
 
+    /// Get the output-configured pins and their commanded levels, in pin order.
+    pub fn output_pins(&mut self) -> Result<impl Iterator<Item = (Pin, PinLevel)>, T::Error> {
+        let config = self.read_port_config()?;
+        let output = self.read_output_port()?;
+        Ok((0..8u8)
+            .filter(move |&pin| config & (1 << pin) == 0)
+            .map(move |pin| (pin, PinLevel::from_port(output, pin))))
+    }
+
+    /// Get the commanded level of an output pin.
+    ///
+    /// This reads the output latch (Output Port register), i.e. the level the pin was
+    /// told to drive. It can differ from `read_pin_input`, which senses the actual pin:
+    /// for a pin configured as input, or an output pulled against its driven level.
+    pub fn get_pin_output(&mut self, pin: u8) -> Result<PinLevel, T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        self.check_pin(pin)?;
+
+        let port_value = self.read_output_port()?;
+        Ok(PinLevel::from_port(port_value, pin))
+    }
+
     /// Set a specific output pin.
     pub fn set_pin_output(&mut self, pin: u8, level: PinLevel) -> Result<(), T::Error>
     where
         T::Error: From<Tca9534CoreError>,
     {
-        if pin > 7 {
-            return Err(Tca9534CoreError::InvalidPin.into());
-        }
+        self.check_pin(pin)?;
 
-        let mut current_value = self.read_output_port()?;
-        match level {
-            PinLevel::High => current_value |= 1 << pin,
-            PinLevel::Low => current_value &= !(1 << pin),
-        }
-        self.write_output_port(current_value)
+        self.modify_register(Register::OutputPort, |value| match level {
+            PinLevel::High => value | (1 << pin),
+            PinLevel::Low => value & !(1 << pin),
+        })?;
+        Ok(())
     }
 
     /// Toggle a specific output pin.
@@ -123,13 +1636,39 @@ where
     where
         T::Error: From<Tca9534CoreError>,
     {
-        if pin > 7 {
-            return Err(Tca9534CoreError::InvalidPin.into());
+        self.check_pin(pin)?;
+
+        self.modify_register(Register::OutputPort, |value| value ^ (1 << pin))?;
+        Ok(())
+    }
+
+    /// Drive an output pin to the level of an input pin, inverted if `invert` is set.
+    ///
+    /// Reads the Input Port, then updates the output bit with one Output Port
+    /// read-modify-write. Returns the level driven on `out_pin`. The inversion is
+    /// applied in software: the Polarity Inversion register is not touched, but
+    /// already applies to the Input Port value read for `in_pin`.
+    pub fn mirror(&mut self, in_pin: u8, out_pin: u8, invert: bool) -> Result<PinLevel, T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        self.check_pin(in_pin)?;
+        self.check_pin(out_pin)?;
+        if in_pin == out_pin {
+            return Err(Tca9534CoreError::InvalidArgument.into());
         }
 
-        let mut current_value = self.read_output_port()?;
-        current_value ^= 1 << pin;
-        self.write_output_port(current_value)
+        let input = self.read_register(Register::InputPort)?;
+        let high = (PinLevel::from_port(input, in_pin) == PinLevel::High) != invert;
+        let mut output = self.read_output_port()?;
+        if high {
+            output |= 1 << out_pin;
+        } else {
+            output &= !(1 << out_pin);
+        }
+        self.write_output_port(output)?;
+
+        Ok(if high { PinLevel::High } else { PinLevel::Low })
     }
 
     /// Configure pin direction (input/output).
@@ -137,16 +1676,64 @@ where
     where
         T::Error: From<Tca9534CoreError>,
     {
-        if pin > 7 {
-            return Err(Tca9534CoreError::InvalidPin.into());
-        }
+        self.check_pin(pin)?;
 
-        let mut current_config = self.read_register(Register::Config)?;
-        match config {
-            PinConfig::Input => current_config |= 1 << pin,
-            PinConfig::Output => current_config &= !(1 << pin),
+        self.modify_register(Register::Config, |value| match config {
+            PinConfig::Input => value | (1 << pin),
+            PinConfig::Output => value & !(1 << pin),
+        })?;
+        Ok(())
+    }
+
+    /// Turn a pin into an output driving `level`, without a glitch.
+    ///
+    /// The Output Port bit is written before the Config bit, so the pin never drives
+    /// the stale output latch value.
+    pub fn transition_to_output(&mut self, pin: u8, level: PinLevel) -> Result<(), T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        self.set_pin_output(pin, level)?;
+        self.set_pin_config(pin, PinConfig::Output)
+    }
+
+    /// Pulse a reset line: drive `pin` to `active` for `width_us` microseconds, then release it.
+    ///
+    /// The pin is turned into an output already driving `active` (see
+    /// [`transition_to_output`](Self::transition_to_output)) and stays an output driving
+    /// the opposite level afterwards. Pass [`PinLevel::Low`] for the usual active-low
+    /// RESET input, [`PinLevel::High`] for an active-high one.
+    #[cfg(feature = "embedded-hal")]
+    pub fn assert_reset(
+        &mut self,
+        pin: u8,
+        active: PinLevel,
+        delay: &mut impl DelayNs,
+        width_us: u32,
+    ) -> Result<(), T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        let released = match active {
+            PinLevel::Low => PinLevel::High,
+            PinLevel::High => PinLevel::Low,
+        };
+        self.transition_to_output(pin, active)?;
+        delay.delay_us(width_us);
+        self.set_pin_output(pin, released)
+    }
+
+    /// Drive the outputs to a safe state before power-down.
+    ///
+    /// Writes `safe_output` to the Output Port, then, with `high_z`, makes every pin an
+    /// input so nothing is driven. The Config write comes last, so outputs pass through
+    /// the safe state rather than their previous levels.
+    pub fn park(&mut self, safe_output: u8, high_z: bool) -> Result<(), T::Error> {
+        self.write_output_port(safe_output)?;
+        if high_z {
+            self.set_port_config(config::ALL_INPUTS)?;
         }
-        self.write_register(Register::Config, current_config)
+        Ok(())
     }
 
     /// Configure all pins direction at once.
@@ -164,16 +1751,16 @@ where
     where
         T::Error: From<Tca9534CoreError>,
     {
-        if pin > 7 {
-            return Err(Tca9534CoreError::InvalidPin.into());
-        }
+        self.check_pin(pin)?;
 
-        let mut current_polarity = self.read_register(Register::Polarity)?;
-        match polarity {
-            PinPolarity::Normal => current_polarity &= !(1 << pin),
-            PinPolarity::Inverted => current_polarity |= 1 << pin,
+        let value = self.modify_register(Register::Polarity, |value| match polarity {
+            PinPolarity::Normal => value & !(1 << pin),
+            PinPolarity::Inverted => value | (1 << pin),
+        })?;
+        if self.verify_polarity {
+            self.check_polarity(value)?;
         }
-        self.write_register(Register::Polarity, current_polarity)
+        Ok(())
     }
 
     /// Configure all pins polarity at once.
@@ -181,8 +1768,247 @@ where
         self.write_register(Register::Polarity, polarity)
     }
 
+    /// Configure all pins polarity at once and read the register back.
+    ///
+    /// Fails with [`Tca9534CoreError::PolarityMismatch`] if the device did not take the
+    /// value, whether or not polarity verification is enabled.
+    pub fn set_port_polarity_verified(&mut self, polarity: u8) -> Result<(), T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        self.write_register(Register::Polarity, polarity)?;
+        self.check_polarity(polarity)
+    }
+
+    /// Check that the Polarity register holds `polarity`.
+    fn check_polarity(&mut self, polarity: u8) -> Result<(), T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        if self.read_register(Register::Polarity)? != polarity {
+            return Err(Tca9534CoreError::PolarityMismatch.into());
+        }
+        Ok(())
+    }
+
     /// Read port polarity configuration.
     pub fn read_port_polarity(&mut self) -> Result<u8, T::Error> {
         self.read_register(Register::Polarity)
     }
+
+    /// Run a loopback test over externally wired (output, input) pin pairs.
+    ///
+    /// For each pair, the output pin is driven low then high and the input pin is read
+    /// back after `settle_us` microseconds, compensating for the Polarity register so
+    /// the reported levels are the electrical ones. The Output Port and Config registers
+    /// are restored afterwards, even if a transfer fails.
+    ///
+    /// At most [`LoopbackReport::CAPACITY`] pairs can be tested in one run.
+    #[cfg(feature = "embedded-hal")]
+    pub fn loopback_test(
+        &mut self,
+        pairs: &[(u8, u8)],
+        delay: &mut impl DelayNs,
+        settle_us: u32,
+    ) -> Result<LoopbackReport, T::Error>
+    where
+        T::Error: From<Tca9534CoreError>,
+    {
+        if pairs.len() > LoopbackReport::CAPACITY {
+            return Err(Tca9534CoreError::InvalidArgument.into());
+        }
+        for &(output, input) in pairs {
+            self.check_pin(output)?;
+            self.check_pin(input)?;
+            if output == input {
+                return Err(Tca9534CoreError::InvalidArgument.into());
+            }
+        }
+
+        let config = self.read_port_config()?;
+        let output = self.read_output_port()?;
+        let polarity = self.read_port_polarity()?;
+
+        let report = self.run_loopback(pairs, config, output, polarity, delay, settle_us);
+
+        // Restore the outputs before the directions to avoid glitches
+        self.write_output_port(output)?;
+        self.set_port_config(config)?;
+
+        report
+    }
+
+    #[cfg(feature = "embedded-hal")]
+    fn run_loopback(
+        &mut self,
+        pairs: &[(u8, u8)],
+        config: u8,
+        output: u8,
+        polarity: u8,
+        delay: &mut impl DelayNs,
+        settle_us: u32,
+    ) -> Result<LoopbackReport, T::Error> {
+        let mut report = LoopbackReport::new();
+        for &(out_pin, in_pin) in pairs {
+            let mut observed = [PinLevel::Low; 2];
+            for (value, observed) in [output & !(1 << out_pin), output | (1 << out_pin)]
+                .into_iter()
+                .zip(&mut observed)
+            {
+                self.write_output_port(value)?;
+                self.set_port_config((config & !(1 << out_pin)) | (1 << in_pin))?;
+                delay.delay_us(settle_us);
+                let input = self.read_register(Register::InputPort)?;
+                *observed = PinLevel::from_port(input ^ polarity, in_pin);
+            }
+            report.push(LoopbackResult {
+                output: out_pin,
+                input: in_pin,
+                observed_low: observed[0],
+                observed_high: observed[1],
+            });
+        }
+        Ok(report)
+    }
+
+    /// Light each output in turn (0x01, 0x02, ... 0x80), e.g. LEDs in a production test.
+    ///
+    /// Only the Output Port is written: the pins must already be outputs. Each state is
+    /// held for `dwell_us` microseconds; with `verify`, the Output Port register is read
+    /// back and the first mismatching state is returned. Pins not wired on the board
+    /// (see `new_with_usable_pins`) are skipped and keep their output level. The
+    /// previous Output Port value is restored afterwards, even on failure.
+    #[cfg(feature = "embedded-hal")]
+    pub fn walking_ones(
+        &mut self,
+        delay: &mut impl DelayNs,
+        dwell_us: u32,
+        verify: bool,
+    ) -> Result<Option<PatternMismatch>, T::Error> {
+        self.walk_outputs(&WALKING_PATTERN[..8], delay, dwell_us, verify)
+    }
+
+    /// Clear each output in turn (0xFE, 0xFD, ... 0x7F); see `walking_ones`.
+    #[cfg(feature = "embedded-hal")]
+    pub fn walking_zeros(
+        &mut self,
+        delay: &mut impl DelayNs,
+        dwell_us: u32,
+        verify: bool,
+    ) -> Result<Option<PatternMismatch>, T::Error> {
+        self.walk_outputs(&WALKING_PATTERN[8..], delay, dwell_us, verify)
+    }
+
+    /// Take the bits of `state` for the pins wired on the board and the others from `base`.
+    #[cfg(feature = "embedded-hal")]
+    fn pattern_state(&self, state: u8, base: u8) -> u8 {
+        (state & self.usable_pins) | (base & !self.usable_pins)
+    }
+
+    #[cfg(feature = "embedded-hal")]
+    fn walk_outputs(
+        &mut self,
+        states: &[u8],
+        delay: &mut impl DelayNs,
+        dwell_us: u32,
+        verify: bool,
+    ) -> Result<Option<PatternMismatch>, T::Error> {
+        let output = self.read_output_port()?;
+        let result = self.write_states(states, output, delay, dwell_us, verify);
+        self.write_output_port(output)?;
+        result
+    }
+
+    #[cfg(feature = "embedded-hal")]
+    fn write_states(
+        &mut self,
+        states: &[u8],
+        output: u8,
+        delay: &mut impl DelayNs,
+        dwell_us: u32,
+        verify: bool,
+    ) -> Result<Option<PatternMismatch>, T::Error> {
+        for (index, &state) in states.iter().enumerate() {
+            // Each state walks one pin, in pin order
+            if self.usable_pins & (1 << (index % 8)) == 0 {
+                continue;
+            }
+            let expected = self.pattern_state(state, output);
+            self.write_output_port(expected)?;
+            delay.delay_us(dwell_us);
+            if verify {
+                let observed = self.read_output_port()?;
+                if observed != expected {
+                    return Ok(Some(PatternMismatch { expected, observed }));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Drive the [`WALKING_PATTERN`] on all pins, e.g. to find solder bridges.
+    ///
+    /// All pins are configured as outputs and each state is held for `dwell_us`
+    /// microseconds. With `verify`, each state is read back through the Input Port
+    /// (polarity compensated) and the first mismatching state is returned. Pins not
+    /// wired on the board (see `new_with_usable_pins`) are skipped: their direction
+    /// and output level are left unchanged, and they never count as mismatching.
+    /// The Output Port and Config registers are restored afterwards, even on failure.
+    ///
+    /// Only use this on boards where all wired pins can safely be driven.
+    #[cfg(feature = "embedded-hal")]
+    pub fn self_test_pattern(
+        &mut self,
+        delay: &mut impl DelayNs,
+        dwell_us: u32,
+        verify: bool,
+    ) -> Result<Option<PatternMismatch>, T::Error> {
+        let config = self.read_port_config()?;
+        let output = self.read_output_port()?;
+        let polarity = self.read_port_polarity()?;
+
+        let result = self.run_pattern(config, output, polarity, delay, dwell_us, verify);
+
+        // Restore the outputs before the directions to avoid glitches
+        self.write_output_port(output)?;
+        self.set_port_config(config)?;
+
+        result
+    }
+
+    #[cfg(feature = "embedded-hal")]
+    fn run_pattern(
+        &mut self,
+        config: u8,
+        output: u8,
+        polarity: u8,
+        delay: &mut impl DelayNs,
+        dwell_us: u32,
+        verify: bool,
+    ) -> Result<Option<PatternMismatch>, T::Error> {
+        let mut first = true;
+        for (index, &state) in WALKING_PATTERN.iter().enumerate() {
+            // Each state walks one pin, in pin order
+            if self.usable_pins & (1 << (index % 8)) == 0 {
+                continue;
+            }
+            let expected = self.pattern_state(state, output);
+            self.write_output_port(expected)?;
+            if first {
+                first = false;
+                let outputs = self.pattern_state(config::ALL_OUTPUTS, config);
+                self.set_port_config(outputs)?;
+            }
+            delay.delay_us(dwell_us);
+            if verify {
+                let input = self.read_register(Register::InputPort)? ^ polarity;
+                // Undriven reserved pins count as following the pattern
+                let observed = self.pattern_state(input, expected);
+                if observed != expected {
+                    return Ok(Some(PatternMismatch { expected, observed }));
+                }
+            }
+        }
+        Ok(None)
+    }
 }
@@ -0,0 +1,203 @@
+use super::tca9534_sync::Tca9534;
+#[cfg(feature = "embedded-hal")]
+use crate::initial_config::StartupOptions;
+use crate::initial_config::{InitMode, InitialConfig};
+use crate::registers::*;
+use crate::transport::SyncTransport;
+#[cfg(feature = "embedded-hal")]
+use embedded_hal::delay::DelayNs;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// How the synchronous driver holds its address: [`RuntimeAddress`] or [`FixedAddress`].
+///
+/// Every driver method is available in both modes, except `set_address`,
+/// `with_address` and `switch_device`, which only exist with a runtime address.
+pub trait AddressMode: sealed::Sealed {
+    /// The address named by the type, if any.
+    const FIXED: Option<u8>;
+}
+
+/// The address is chosen at runtime and can be changed, see [`Tca9534Sync`](crate::Tca9534Sync).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuntimeAddress;
+
+/// The address is `ADDR`, see [`Tca9534SyncAt`](crate::Tca9534SyncAt).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedAddress<const ADDR: u8>;
+
+impl sealed::Sealed for RuntimeAddress {}
+
+impl AddressMode for RuntimeAddress {
+    const FIXED: Option<u8> = None;
+}
+
+impl<const ADDR: u8> sealed::Sealed for FixedAddress<ADDR> {}
+
+impl<const ADDR: u8> AddressMode for FixedAddress<ADDR> {
+    const FIXED: Option<u8> = Some(ADDR);
+}
+
+impl<const ADDR: u8> FixedAddress<ADDR> {
+    /// Fails to compile for an address outside every supported family window.
+    const VALID: () = {
+        let mut index = 0;
+        let mut valid = false;
+        while index < Family::ALL.len() {
+            valid |= Family::ALL[index].contains(ADDR);
+            index += 1;
+        }
+        assert!(
+            valid,
+            "address outside the window of every supported family"
+        );
+    };
+}
+
+/// Synchronous driver with the device address in its type.
+///
+/// With several expanders on a bus, typed board APIs can then only be handed the right
+/// one. The address is checked against the [`Family::ALL`] windows at compile time.
+/// This is the synchronous driver itself with a [`FixedAddress`] mode, so it offers
+/// every driver method except the address changes (`set_address`, `with_address`,
+/// `switch_device`); convert it into a [`Tca9534Sync`](crate::Tca9534Sync) to
+/// readdress the driver.
+///
+/// ```rust
+/// # fn check<T: tca9534_driver_rs::SyncTransport>(front: &mut tca9534_driver_rs::Tca9534SyncAt<T, 0x20>) {
+/// fn front_panel<T: tca9534_driver_rs::SyncTransport>(_tca: &mut tca9534_driver_rs::Tca9534SyncAt<T, 0x20>) {}
+/// front_panel(front);
+/// # }
+/// ```
+///
+/// Passing the expander at another address does not compile:
+///
+/// ```rust,compile_fail
+/// # fn check<T: tca9534_driver_rs::SyncTransport>(keypad: &mut tca9534_driver_rs::Tca9534SyncAt<T, 0x21>) {
+/// fn front_panel<T: tca9534_driver_rs::SyncTransport>(_tca: &mut tca9534_driver_rs::Tca9534SyncAt<T, 0x20>) {}
+/// front_panel(keypad);
+/// # }
+/// ```
+///
+/// Nor does readdressing it in place:
+///
+/// ```rust,compile_fail
+/// # fn check<T: tca9534_driver_rs::SyncTransport>(front: &mut tca9534_driver_rs::Tca9534SyncAt<T, 0x20>) {
+/// front.set_address(0x21);
+/// # }
+/// ```
+///
+/// Neither does an address outside every family window:
+///
+/// ```rust,compile_fail
+/// # struct Bus;
+/// # impl tca9534_driver_rs::SyncTransport for Bus {
+/// #     type Error = ();
+/// #     fn write(&mut self, _: u8, _: &[u8]) -> Result<(), ()> { Ok(()) }
+/// #     fn read(&mut self, _: u8, _: &mut [u8]) -> Result<(), ()> { Ok(()) }
+/// #     fn write_read(&mut self, _: u8, _: &[u8], _: &mut [u8]) -> Result<(), ()> { Ok(()) }
+/// # }
+/// let tca = tca9534_driver_rs::Tca9534SyncAt::<Bus, 0x50>::new_without_init(Bus);
+/// ```
+pub type Tca9534At<T, const ADDR: u8> = Tca9534<T, FixedAddress<ADDR>>;
+
+/// Synchronous driver with a runtime address.
+pub type Tca9534Runtime<T> = Tca9534<T, RuntimeAddress>;
+
+/// Constructors at `ADDR`, matching the runtime-address ones.
+///
+/// Other constructors, such as `auto_detect`, pick the address at runtime: convert
+/// their result with `TryFrom`.
+impl<T, const ADDR: u8> Tca9534At<T, ADDR>
+where
+    T: SyncTransport,
+{
+    /// Create a new TCA9534 driver instance at `ADDR`.
+    pub fn new(transport: T) -> Result<Self, T::Error> {
+        let () = FixedAddress::<ADDR>::VALID;
+        Tca9534Runtime::new(transport, ADDR).map(Tca9534::into_mode)
+    }
+
+    /// Create a new TCA9534 driver instance at `ADDR` without writing to the device.
+    pub fn new_without_init(transport: T) -> Self {
+        let () = FixedAddress::<ADDR>::VALID;
+        Tca9534Runtime::new_without_init(transport, ADDR).into_mode()
+    }
+
+    /// Create a new TCA9534 driver instance at `ADDR`, keeping the power-on Output Port default.
+    pub fn new_with_por_output(transport: T) -> Result<Self, T::Error> {
+        let () = FixedAddress::<ADDR>::VALID;
+        Tca9534Runtime::new_with_por_output(transport, ADDR).map(Tca9534::into_mode)
+    }
+
+    /// Create a new TCA9534 driver instance at `ADDR`, initializing it in the given mode.
+    pub fn new_with_mode(transport: T, mode: InitMode) -> Result<Self, T::Error> {
+        let () = FixedAddress::<ADDR>::VALID;
+        Tca9534Runtime::new_with_mode(transport, ADDR, mode).map(Tca9534::into_mode)
+    }
+
+    /// Create a new TCA9534 driver instance at `ADDR`, writing only the registers that need it.
+    pub fn new_minimal(transport: T) -> Result<Self, T::Error> {
+        let () = FixedAddress::<ADDR>::VALID;
+        Tca9534Runtime::new_minimal(transport, ADDR).map(Tca9534::into_mode)
+    }
+
+    /// Create a new TCA9534 driver instance at `ADDR`, retrying `init()` while the device powers up.
+    #[cfg(feature = "embedded-hal")]
+    pub fn new_with_startup(
+        transport: T,
+        options: StartupOptions,
+        delay: &mut impl DelayNs,
+    ) -> Result<Self, (T, T::Error)> {
+        let () = FixedAddress::<ADDR>::VALID;
+        Tca9534Runtime::new_with_startup(transport, ADDR, options, delay).map(Tca9534::into_mode)
+    }
+
+    /// Create a new TCA9534 driver instance at `ADDR` with a custom startup configuration.
+    pub fn new_with_config(transport: T, initial: &InitialConfig) -> Result<Self, T::Error> {
+        let () = FixedAddress::<ADDR>::VALID;
+        Tca9534Runtime::new_with_config(transport, ADDR, initial).map(Tca9534::into_mode)
+    }
+
+    /// Create a new TCA9534 driver instance at `ADDR` with a per-pin startup state.
+    pub fn new_with_pin_table(
+        transport: T,
+        table: &[(PinConfig, PinLevel); 8],
+    ) -> Result<Self, T::Error> {
+        let () = FixedAddress::<ADDR>::VALID;
+        Tca9534Runtime::new_with_pin_table(transport, ADDR, table).map(Tca9534::into_mode)
+    }
+
+    /// Create a new TCA9534 driver instance at `ADDR` for a board wiring only some of the pins.
+    pub fn new_with_usable_pins(transport: T, usable: u8) -> Result<Self, T::Error> {
+        let () = FixedAddress::<ADDR>::VALID;
+        Tca9534Runtime::new_with_usable_pins(transport, ADDR, usable).map(Tca9534::into_mode)
+    }
+}
+
+impl<T, const ADDR: u8> From<Tca9534At<T, ADDR>> for Tca9534Runtime<T>
+where
+    T: SyncTransport,
+{
+    fn from(typed: Tca9534At<T, ADDR>) -> Self {
+        typed.into_mode()
+    }
+}
+
+/// Accepts a driver whose address is `ADDR`, or returns it unchanged.
+impl<T, const ADDR: u8> TryFrom<Tca9534Runtime<T>> for Tca9534At<T, ADDR>
+where
+    T: SyncTransport,
+{
+    type Error = Tca9534Runtime<T>;
+
+    fn try_from(driver: Tca9534Runtime<T>) -> Result<Self, Self::Error> {
+        let () = FixedAddress::<ADDR>::VALID;
+        if driver.address() != ADDR {
+            return Err(driver);
+        }
+        Ok(driver.into_mode())
+    }
+}
@@ -97,19 +97,108 @@ where
     }
 }
 
+/// Async transport wrapper bounding every transaction with a timeout.
+///
+/// Each `write`, `read` and `write_read` is raced against an `embassy-time` timer;
+/// a transaction that does not complete in time is dropped and fails with
+/// [`Tca9534CoreError::Timeout`](crate::Tca9534CoreError::Timeout). Driver methods
+/// issuing several transactions get the full timeout for each one. Without a
+/// timeout (the default) transactions are awaited unbounded.
+///
+/// The timeout is usually set through
+/// [`Tca9534Async::set_operation_timeout`](crate::Tca9534Async::set_operation_timeout).
+#[cfg(feature = "embassy-time")]
+#[derive(Debug)]
+pub struct Deadline<T> {
+    transport: T,
+    timeout: Option<embassy_time::Duration>,
+}
+
+#[cfg(feature = "embassy-time")]
+impl<T> Deadline<T> {
+    /// Wrap a transport, without a timeout.
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            timeout: None,
+        }
+    }
+
+    /// Set the per-transaction timeout; `None` waits unbounded.
+    pub fn set_timeout(&mut self, timeout: Option<embassy_time::Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Get the per-transaction timeout.
+    pub fn timeout(&self) -> Option<embassy_time::Duration> {
+        self.timeout
+    }
+
+    /// Release the wrapped transport.
+    pub fn into_inner(self) -> T {
+        self.transport
+    }
+
+    /// Await a transaction, bounded by the timeout if one is set.
+    async fn bounded<R, E>(
+        timeout: Option<embassy_time::Duration>,
+        transaction: impl core::future::Future<Output = Result<R, E>>,
+    ) -> Result<R, E>
+    where
+        E: From<crate::error::Tca9534CoreError>,
+    {
+        match timeout {
+            Some(timeout) => embassy_time::with_timeout(timeout, transaction)
+                .await
+                .unwrap_or(Err(crate::error::Tca9534CoreError::Timeout.into())),
+            None => transaction.await,
+        }
+    }
+}
+
+#[cfg(feature = "embassy-time")]
+impl<T> AsyncTransport for Deadline<T>
+where
+    T: AsyncTransport,
+    T::Error: From<crate::error::Tca9534CoreError>,
+{
+    type Error = T::Error;
+
+    async fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        Self::bounded(self.timeout, self.transport.write(addr, bytes)).await
+    }
+
+    async fn read(&mut self, addr: u8, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        Self::bounded(self.timeout, self.transport.read(addr, bytes)).await
+    }
+
+    async fn write_read(
+        &mut self,
+        addr: u8,
+        wr_bytes: &[u8],
+        rd_bytes: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        Self::bounded(
+            self.timeout,
+            self.transport.write_read(addr, wr_bytes, rd_bytes),
+        )
+        .await
+    }
+}
+
 // #[cfg(feature = "async")]
 // impl<I2C> AsyncTransport for embedded_hal_async::i2c::I2cDevice
 // where
 //     I2C: embedded_hal_async::i2c::I2c,
 // {
-//     type Error = TCA9534Error<I2C::Error>;
+//     type Error = Tca9534Error<I2C::Error>;
 
 //     async fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Self::Error> {
-//         self.i2c.write(addr, bytes).await.map_err(TCA9534Error::I2c)
+//         self.i2c.write(addr, bytes).await.map_err(Tca9534Error::I2c)
 //     }
 
 //     async fn read(&mut self, addr: u8, bytes: &mut [u8]) -> Result<(), Self::Error> {
-//         self.i2c.read(addr, bytes).await.map_err(TCA9534Error::I2c)
+//         self.i2c.read(addr, bytes).await.map_err(Tca9534Error::I2c)
 //     }
 
 //     async fn write_read(
@@ -118,6 +207,6 @@ where
 //         wr_bytes: &[u8],
 //         rd_bytes: &mut [u8],
 //     ) -> Result<(), Self::Error> {
-//         self.i2c.write_read(addr, wr_bytes, rd_bytes).await.map_err(TCA9534Error::I2c)
+//         self.i2c.write_read(addr, wr_bytes, rd_bytes).await.map_err(Tca9534Error::I2c)
 //     }
 // }
@@ -0,0 +1,651 @@
+#![cfg(all(feature = "async", feature = "embedded-hal-async"))]
+
+mod common;
+
+use core::future::Future;
+use core::pin::pin;
+use core::task::{Context, Waker};
+
+use common::*;
+use embedded_hal::i2c::ErrorKind;
+use embedded_hal_mock::eh1::delay::NoopDelay;
+use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+use tca9534_driver_rs::{
+    config, AsyncIoExpander, DegradedPolicy, Family, InitMode, InitialConfig, PinConfig, PinLevel,
+    PinPolarity, Port, Register, StaggerOrder, StartupOptions, Tca9534Async, Tca9534CoreError,
+    Tca9534Error, INIT_SEQUENCE, WALKING_PATTERN,
+};
+
+fn driver(rest: &[Transaction]) -> (Tca9534Async<Mock>, Mock) {
+    let i2c = Mock::new(&after_init(ADDR, rest));
+    let tca = block_on(Tca9534Async::new(i2c.clone(), ADDR)).unwrap();
+    (tca, i2c)
+}
+
+#[test]
+fn new_writes_init_sequence() {
+    let (tca, mut i2c) = driver(&[]);
+    assert_eq!(tca.address(), ADDR);
+    i2c.done();
+}
+
+#[test]
+fn new_with_por_output_keeps_output_high() {
+    let expectations = [
+        write_reg(ADDR, Register::Config, 0xFF),
+        write_reg(ADDR, Register::OutputPort, config::POR_OUTPUT_DEFAULT),
+        write_reg(ADDR, Register::Polarity, 0x00),
+        read_reg(ADDR, Register::InputPort, 0x00),
+    ];
+    let mut i2c = Mock::new(&expectations);
+    block_on(Tca9534Async::new_with_por_output(i2c.clone(), ADDR)).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn new_minimal_skips_registers_already_at_init_values() {
+    let expectations = [
+        read_reg(ADDR, Register::Config, 0xFF),
+        read_reg(ADDR, Register::OutputPort, 0x00),
+        read_reg(ADDR, Register::Polarity, 0x00),
+        read_reg(ADDR, Register::InputPort, 0x00),
+    ];
+    let mut i2c = Mock::new(&expectations);
+    block_on(Tca9534Async::new_minimal(i2c.clone(), ADDR)).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn minimal_traffic_init_writes_only_output() {
+    let mut i2c = Mock::new(&[
+        write_reg(ADDR, Register::OutputPort, 0x00),
+        read_reg(ADDR, Register::InputPort, 0x00),
+    ]);
+    block_on(Tca9534Async::new_with_mode(
+        i2c.clone(),
+        ADDR,
+        InitMode::MinimalTraffic,
+    ))
+    .unwrap();
+    i2c.done();
+}
+
+#[test]
+fn apply_config_writes_only_changed_registers() {
+    let (mut tca, mut i2c) = driver(&[write_reg(ADDR, Register::Polarity, 0b1000_0000)]);
+    let report = block_on(tca.apply_config(&InitialConfig::new().with_inverted(&[7]))).unwrap();
+    assert!(report.polarity);
+    assert_eq!(report.count(), 1);
+    i2c.done();
+}
+
+#[test]
+fn embedded_hal_async_transport_error_is_the_public_error_type() {
+    let (mut tca, mut i2c) =
+        driver(&[read_reg(ADDR, Register::InputPort, 0x00).with_error(ErrorKind::Bus)]);
+    let result: Result<u8, Tca9534Error<ErrorKind>> = block_on(tca.read_input_port());
+    assert!(matches!(result, Err(Tca9534Error::I2c(ErrorKind::Bus))));
+    i2c.done();
+}
+
+#[test]
+fn write_output_staggered_writes_one_pin_per_step() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::OutputPort, 0x00),
+        write_reg(ADDR, Register::OutputPort, 0x80),
+        write_reg(ADDR, Register::OutputPort, 0xC0),
+    ]);
+    block_on(tca.write_output_staggered(0xC0, StaggerOrder::MsbFirst, 50, &mut NoopDelay)).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn modify_register_applies_closure() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::Polarity, 0x0F),
+        write_reg(ADDR, Register::Polarity, 0xF0),
+    ]);
+    let value = block_on(tca.modify_register(Register::Polarity, |value| !value)).unwrap();
+    assert_eq!(value, 0xF0);
+    assert!(matches!(
+        block_on(tca.modify_register(Register::InputPort, |value| value)),
+        Err(Tca9534Error::Core(Tca9534CoreError::InvalidArgument))
+    ));
+    i2c.done();
+}
+
+#[test]
+fn pin_setters_issue_one_read_and_one_write() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::OutputPort, 0b0000_0001),
+        write_reg(ADDR, Register::OutputPort, 0b0000_0101),
+        read_reg(ADDR, Register::OutputPort, 0b0000_0101),
+        write_reg(ADDR, Register::OutputPort, 0b0000_0100),
+        read_reg(ADDR, Register::Config, 0xFF),
+        write_reg(ADDR, Register::Config, 0b1111_1011),
+        read_reg(ADDR, Register::Polarity, 0x00),
+        write_reg(ADDR, Register::Polarity, 0b1000_0000),
+    ]);
+    block_on(tca.set_pin_output(2, PinLevel::High)).unwrap();
+    block_on(tca.toggle_pin_output(0)).unwrap();
+    block_on(tca.set_pin_config(2, PinConfig::Output)).unwrap();
+    block_on(tca.set_pin_polarity(7, PinPolarity::Inverted)).unwrap();
+    i2c.done();
+}
+
+#[cfg(feature = "embedded-hal")]
+#[test]
+fn sync_and_async_drivers_issue_the_same_transactions() {
+    let expectations = after_init(
+        ADDR,
+        &[
+            read_reg(ADDR, Register::OutputPort, 0x00),
+            write_reg(ADDR, Register::OutputPort, 0x04),
+            read_reg(ADDR, Register::OutputPort, 0x04),
+            write_reg(ADDR, Register::OutputPort, 0x05),
+            read_reg(ADDR, Register::Config, 0xFF),
+            write_reg(ADDR, Register::Config, 0xFB),
+            read_reg(ADDR, Register::Polarity, 0x00),
+            write_reg(ADDR, Register::Polarity, 0x80),
+            // The Output Port was changed behind the driver's back
+            read_reg(ADDR, Register::OutputPort, 0x35),
+            write_reg(ADDR, Register::OutputPort, 0x05),
+            read_reg(ADDR, Register::OutputPort, 0x05),
+            write_reg(ADDR, Register::OutputPort, 0x85),
+            read_reg(ADDR, Register::InputPort, 0x02),
+            read_reg(ADDR, Register::OutputPort, 0x85),
+            write_reg(ADDR, Register::OutputPort, 0x8D),
+            read_reg(ADDR, Register::Config, 0xFB),
+            write_reg(ADDR, Register::Config, 0x04),
+        ],
+    );
+    let mask = Port::pin(4) | Port::pin(5);
+
+    let mut i2c = Mock::new(&expectations);
+    let mut tca = tca9534_driver_rs::Tca9534Sync::new(i2c.clone(), ADDR).unwrap();
+    tca.set_pin_output(2, PinLevel::High).unwrap();
+    tca.toggle_pin_output(0).unwrap();
+    tca.set_pin_config(2, PinConfig::Output).unwrap();
+    tca.set_pin_polarity(7, PinPolarity::Inverted).unwrap();
+    tca.set_pins(mask, PinLevel::Low).unwrap();
+    tca.write_output_masked(Port(0x80), Port(0xFF)).unwrap();
+    tca.mirror(1, 3, false).unwrap();
+    tca.modify_register(Register::Config, |value| !value)
+        .unwrap();
+    i2c.done();
+
+    let mut i2c = Mock::new(&expectations);
+    let mut tca = block_on(Tca9534Async::new(i2c.clone(), ADDR)).unwrap();
+    block_on(tca.set_pin_output(2, PinLevel::High)).unwrap();
+    block_on(tca.toggle_pin_output(0)).unwrap();
+    block_on(tca.set_pin_config(2, PinConfig::Output)).unwrap();
+    block_on(tca.set_pin_polarity(7, PinPolarity::Inverted)).unwrap();
+    block_on(tca.set_pins(mask, PinLevel::Low)).unwrap();
+    block_on(tca.write_output_masked(Port(0x80), Port(0xFF))).unwrap();
+    block_on(tca.mirror(1, 3, false)).unwrap();
+    block_on(tca.modify_register(Register::Config, |value| !value)).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn new_with_startup_retries_until_device_answers() {
+    let mut expectations =
+        vec![write_reg(ADDR, Register::Config, 0xFF).with_error(ErrorKind::Other)];
+    expectations.extend(init_transactions(ADDR, 0x00));
+    let mut i2c = Mock::new(&expectations);
+    let options = StartupOptions {
+        init_attempts: 2,
+        ..StartupOptions::default()
+    };
+    block_on(Tca9534Async::new_with_startup(
+        i2c.clone(),
+        ADDR,
+        options,
+        &mut NoopDelay,
+    ))
+    .unwrap();
+    i2c.done();
+}
+
+#[test]
+fn check_against_reads_without_writing() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::InputPort, 0x00),
+        read_reg(ADDR, Register::OutputPort, 0x00),
+        read_reg(ADDR, Register::Polarity, 0x00),
+        read_reg(ADDR, Register::Config, 0xFE),
+    ]);
+    let expected = InitialConfig::new().with_outputs(&[0, 4], PinLevel::Low);
+    let diff = block_on(tca.check_against(&expected)).unwrap();
+    assert_eq!(diff.config, 0b0001_0000);
+    assert!(!diff.is_conformant());
+    i2c.done();
+}
+
+#[test]
+fn detect_output_faults_compensates_polarity() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::InputPort, 0x00),
+        read_reg(ADDR, Register::OutputPort, 0x81),
+        read_reg(ADDR, Register::Polarity, 0x01),
+        read_reg(ADDR, Register::Config, 0x7E),
+    ]);
+    assert_eq!(block_on(tca.detect_output_faults()).unwrap(), 0b1000_0000);
+    i2c.done();
+}
+
+#[test]
+fn set_pins_takes_one_read_and_one_write() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::OutputPort, 0x10),
+        write_reg(ADDR, Register::OutputPort, 0x1D),
+    ]);
+    block_on(tca.set_pins(Port::pin(0) | Port::pin(2) | Port::pin(3), PinLevel::High)).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn degraded_policy_serves_cached_input() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::InputPort, 0x80),
+        read_reg(ADDR, Register::InputPort, 0x00).with_error(ErrorKind::Other),
+        read_reg(ADDR, Register::InputPort, 0x00).with_error(ErrorKind::Other),
+    ]);
+    tca.set_degraded_policy(DegradedPolicy::ServeCached { max_age: 1 });
+    assert_eq!(block_on(tca.read_input_port()).unwrap(), 0x80);
+    assert_eq!(block_on(tca.read_input_port()).unwrap(), 0x80);
+    assert!(tca.last_read_was_stale());
+    assert!(block_on(tca.read_input_port()).is_err());
+    i2c.done();
+}
+
+#[test]
+fn compare_and_write_output_checks_current_value() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::OutputPort, 0x8F),
+        read_reg(ADDR, Register::OutputPort, 0x0F),
+        write_reg(ADDR, Register::OutputPort, 0x1F),
+    ]);
+    assert_eq!(
+        block_on(tca.compare_and_write_output(0x0F, 0x1F)).unwrap(),
+        Err(0x8F)
+    );
+    assert_eq!(
+        block_on(tca.compare_and_write_output(0x0F, 0x1F)).unwrap(),
+        Ok(())
+    );
+    i2c.done();
+}
+
+#[test]
+fn capture_preinit_state_reads_without_writing() {
+    let mut i2c = Mock::new(&[
+        read_reg(ADDR, Register::InputPort, 0x00),
+        read_reg(ADDR, Register::OutputPort, 0xFF),
+        read_reg(ADDR, Register::Polarity, 0x00),
+        read_reg(ADDR, Register::Config, 0x0F),
+    ]);
+    let captured = block_on(Tca9534Async::capture_preinit_state(&mut i2c.clone(), ADDR)).unwrap();
+    assert_eq!(captured.output, 0xFF);
+    assert_eq!(captured.config, 0x0F);
+    i2c.done();
+}
+
+#[test]
+fn new_with_config_writes_output_before_config() {
+    let initial = InitialConfig::new()
+        .with_outputs(&[7], PinLevel::High)
+        .with_outputs(&[6], PinLevel::Low)
+        .with_inverted(&[0]);
+    let mut i2c = Mock::new(&[
+        write_reg(ADDR, Register::OutputPort, 0x80),
+        write_reg(ADDR, Register::Polarity, 0x01),
+        write_reg(ADDR, Register::Config, 0x3F),
+        read_reg(ADDR, Register::InputPort, 0x01),
+    ]);
+    let tca = block_on(Tca9534Async::new_with_config(i2c.clone(), ADDR, &initial)).unwrap();
+    assert!(tca.is_initialized());
+    i2c.done();
+}
+
+#[test]
+fn reserved_pin_is_rejected_without_bus_traffic() {
+    let mut i2c = Mock::new(&after_init(ADDR, &[]));
+    let mut tca = block_on(Tca9534Async::new_with_usable_pins(i2c.clone(), ADDR, 0x0F)).unwrap();
+    assert!(matches!(
+        block_on(tca.toggle_pin_output(5)),
+        Err(Tca9534Error::Core(Tca9534CoreError::PinReserved))
+    ));
+    assert!(matches!(
+        block_on(tca.set_pins(Port::pin(3) | Port::pin(5), PinLevel::High)),
+        Err(Tca9534Error::Core(Tca9534CoreError::PinReserved))
+    ));
+    assert!(matches!(
+        block_on(tca.write_output_masked(Port(0xF0), Port(0xFF))),
+        Err(Tca9534Error::Core(Tca9534CoreError::PinReserved))
+    ));
+    i2c.done();
+}
+
+#[test]
+fn self_test_pattern_leaves_reserved_pins_alone() {
+    // P4-P7 are not wired: they stay inputs, keep their output bits and read garbage
+    let mut expectations = vec![
+        read_reg(ADDR, Register::Config, 0xF3),
+        read_reg(ADDR, Register::OutputPort, 0x50),
+        read_reg(ADDR, Register::Polarity, 0x00),
+    ];
+    for (index, &state) in WALKING_PATTERN.iter().enumerate() {
+        if index % 8 >= 4 {
+            continue;
+        }
+        let driven = (state & 0x0F) | 0x50;
+        expectations.push(write_reg(ADDR, Register::OutputPort, driven));
+        if index == 0 {
+            expectations.push(write_reg(ADDR, Register::Config, 0xF0));
+        }
+        expectations.push(read_reg(ADDR, Register::InputPort, (driven & 0x0F) | 0xA0));
+    }
+    expectations.push(write_reg(ADDR, Register::OutputPort, 0x50));
+    expectations.push(write_reg(ADDR, Register::Config, 0xF3));
+
+    let mut i2c = Mock::new(&after_init(ADDR, &expectations));
+    let mut tca = block_on(Tca9534Async::new_with_usable_pins(i2c.clone(), ADDR, 0x0F)).unwrap();
+    assert_eq!(
+        block_on(tca.self_test_pattern(&mut NoopDelay, 1, true)).unwrap(),
+        None
+    );
+    i2c.done();
+}
+
+#[test]
+fn try_read_pin_input_separates_core_and_bus_errors() {
+    let (mut tca, mut i2c) =
+        driver(&[read_reg(ADDR, Register::InputPort, 0x00).with_error(ErrorKind::Other)]);
+    assert!(matches!(
+        block_on(tca.try_read_pin_input(8)),
+        Err(Tca9534Error::Core(Tca9534CoreError::InvalidPin))
+    ));
+    assert!(matches!(
+        block_on(tca.try_read_pin_input(0)),
+        Err(Tca9534Error::I2c(Tca9534Error::I2c(ErrorKind::Other)))
+    ));
+    i2c.done();
+}
+
+#[test]
+fn new_with_family_rejects_address_outside_window() {
+    let mut i2c = Mock::new(&[]);
+    let result = block_on(Tca9534Async::new_with_family(
+        i2c.clone(),
+        0x27,
+        Family::Tca9534A,
+    ));
+    assert!(matches!(
+        result,
+        Err(Tca9534Error::Core(Tca9534CoreError::InvalidAddress))
+    ));
+    i2c.done();
+}
+
+#[test]
+fn per_pin_operations_read_modify_write() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::Config, 0xFF),
+        write_reg(ADDR, Register::Config, 0b0111_1111),
+        read_reg(ADDR, Register::OutputPort, 0x00),
+        write_reg(ADDR, Register::OutputPort, 0b1000_0000),
+        read_reg(ADDR, Register::OutputPort, 0b1000_0000),
+        write_reg(ADDR, Register::OutputPort, 0b1000_0010),
+        read_reg(ADDR, Register::Polarity, 0x00),
+        write_reg(ADDR, Register::Polarity, 0b0000_1000),
+        read_reg(ADDR, Register::InputPort, 0b0000_1000),
+    ]);
+    block_on(async {
+        tca.set_pin_config(7, PinConfig::Output).await.unwrap();
+        tca.set_pin_output(7, PinLevel::High).await.unwrap();
+        tca.toggle_pin_output(1).await.unwrap();
+        tca.set_pin_polarity(3, PinPolarity::Inverted)
+            .await
+            .unwrap();
+        assert_eq!(tca.read_pin_input(3).await.unwrap(), PinLevel::High);
+    });
+    i2c.done();
+}
+
+#[test]
+fn invalid_pin_is_rejected_without_bus_traffic() {
+    let (mut tca, mut i2c) = driver(&[]);
+    block_on(async {
+        assert!(matches!(
+            tca.read_pin_input(8).await,
+            Err(Tca9534Error::Core(Tca9534CoreError::InvalidPin))
+        ));
+        assert!(matches!(
+            tca.set_pin_output(8, PinLevel::Low).await,
+            Err(Tca9534Error::Core(Tca9534CoreError::InvalidPin))
+        ));
+        assert!(matches!(
+            tca.set_pin_config(8, PinConfig::Input).await,
+            Err(Tca9534Error::Core(Tca9534CoreError::InvalidPin))
+        ));
+    });
+    i2c.done();
+}
+
+#[test]
+fn write_error_is_reported() {
+    let (mut tca, mut i2c) =
+        driver(&[write_reg(ADDR, Register::OutputPort, 0xAA).with_error(ErrorKind::Other)]);
+    let result = block_on(tca.write_output_port(0xAA));
+    assert!(matches!(result, Err(Tca9534Error::I2c(ErrorKind::Other))));
+    i2c.done();
+}
+
+#[test]
+fn inputs_changed_since_init_compares_with_captured_value() {
+    let mut expectations = init_transactions(ADDR, 0x80);
+    expectations.push(read_reg(ADDR, Register::InputPort, 0x00));
+    let mut i2c = Mock::new(&expectations);
+    let mut tca = block_on(Tca9534Async::new(i2c.clone(), ADDR)).unwrap();
+    assert!(block_on(tca.inputs_changed_since_init()).unwrap());
+    i2c.done();
+}
+
+#[test]
+fn loopback_test_restores_state() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::Config, 0b1111_0000),
+        read_reg(ADDR, Register::OutputPort, 0b0000_1000),
+        read_reg(ADDR, Register::Polarity, 0x00),
+        write_reg(ADDR, Register::OutputPort, 0b0000_1000),
+        write_reg(ADDR, Register::Config, 0b1111_0000),
+        read_reg(ADDR, Register::InputPort, 0b0000_1000),
+        write_reg(ADDR, Register::OutputPort, 0b0000_1001),
+        write_reg(ADDR, Register::Config, 0b1111_0000),
+        read_reg(ADDR, Register::InputPort, 0b0001_1001),
+        write_reg(ADDR, Register::OutputPort, 0b0000_1000),
+        write_reg(ADDR, Register::Config, 0b1111_0000),
+    ]);
+    let report = block_on(tca.loopback_test(&[(0, 4)], &mut NoopDelay, 5)).unwrap();
+    assert!(report.all_passed());
+    i2c.done();
+}
+
+/// Poll a future `polls` times, then drop it; returns whether it completed.
+fn poll_then_drop<F: Future>(future: F, polls: usize) -> bool {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    (0..polls).any(|_| future.as_mut().poll(&mut cx).is_ready())
+}
+
+#[test]
+fn cancelled_set_pin_output_recovers_after_resync() {
+    // One read and one write, each yielding once
+    for polls in 0..4 {
+        let device = FakeDevice::new();
+        device.set_bus(Bus::YieldOnce);
+        let mut tca = Tca9534Async::new_without_init(device.clone(), ADDR);
+        block_on(tca.set_port_config(0x00)).unwrap();
+
+        let completed = poll_then_drop(tca.set_pin_output(3, PinLevel::Low), polls);
+        assert_eq!(completed, polls == 3);
+        let expected = if completed { 0xF7 } else { 0xFF };
+        assert_eq!(device.reg(Register::OutputPort), expected);
+
+        let snapshot = block_on(tca.resync()).unwrap();
+        assert_eq!(snapshot.output, device.reg(Register::OutputPort));
+        block_on(tca.reassert_outputs()).unwrap();
+        assert_eq!(device.reg(Register::OutputPort), snapshot.output);
+
+        block_on(tca.set_pin_output(3, PinLevel::High)).unwrap();
+        assert_eq!(device.reg(Register::OutputPort), 0xFF);
+        block_on(tca.set_pin_output(3, PinLevel::Low)).unwrap();
+        assert_eq!(device.reg(Register::OutputPort), 0xF7);
+    }
+}
+
+#[test]
+fn cancelled_init_is_rerun_by_ensure_initialized() {
+    // 3 writes and 1 read, each yielding once
+    for polls in 0..4 {
+        let device = FakeDevice::new();
+        device.set_bus(Bus::YieldOnce);
+        let mut tca = Tca9534Async::new_without_init(device.clone(), ADDR);
+
+        assert!(!poll_then_drop(tca.init(), polls));
+        assert!(!tca.is_initialized());
+
+        block_on(tca.ensure_initialized()).unwrap();
+        assert!(tca.is_initialized());
+        for &(reg, value) in INIT_SEQUENCE {
+            assert_eq!(device.reg(reg), value);
+        }
+    }
+}
+
+#[test]
+fn auto_detect_in_finds_device_at_last_candidate() {
+    let candidates = [0x20, 0x24, 0x38];
+    let mut expectations: Vec<Transaction> = candidates[..2]
+        .iter()
+        .map(|&addr| read_reg(addr, Register::Config, 0xFF).with_error(ErrorKind::Other))
+        .collect();
+    expectations.push(read_reg(0x38, Register::Config, 0xFF));
+    expectations.extend(init_transactions(0x38, 0x00));
+
+    let mut i2c = Mock::new(&expectations);
+    let tca = block_on(Tca9534Async::auto_detect_in(i2c.clone(), candidates)).unwrap();
+    assert_eq!(tca.address(), 0x38);
+    assert!(tca.is_initialized());
+    i2c.done();
+}
+
+#[test]
+fn auto_detect_returns_transport_when_nothing_responds() {
+    let expectations: Vec<Transaction> = Family::Tca9534A
+        .addresses()
+        .map(|addr| read_reg(addr, Register::Config, 0xFF).with_error(ErrorKind::Other))
+        .collect();
+    let i2c = Mock::new(&expectations);
+    let Err(mut transport) = block_on(Tca9534Async::auto_detect(i2c, &[Family::Tca9534A])) else {
+        panic!("no device should be detected");
+    };
+    transport.done();
+}
+
+#[cfg(feature = "heapless")]
+#[test]
+fn scan_stops_at_capacity() {
+    let mut i2c = Mock::new(&[
+        read_reg(0x38, Register::Config, 0xFF),
+        read_reg(0x39, Register::Config, 0xFF).with_error(ErrorKind::Other),
+        read_reg(0x3A, Register::Config, 0xFF),
+        read_reg(0x3B, Register::Config, 0xFF),
+    ]);
+    let found: heapless::Vec<u8, 2> =
+        block_on(Tca9534Async::scan(&mut i2c, Family::Tca9534A.addresses()));
+    assert_eq!(found.as_slice(), &[0x38, 0x3A]);
+    i2c.done();
+}
+
+async fn blink<E: AsyncIoExpander>(io: &mut E, pin: u8) -> Result<u8, E::Error> {
+    io.set_pin_config(pin, PinConfig::Output).await?;
+    io.set_pin_output(pin, PinLevel::High).await?;
+    io.set_pin_output(pin, PinLevel::Low).await?;
+    io.read_input_port().await
+}
+
+#[test]
+fn generic_expander_code_drives_the_driver() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::Config, 0xFF),
+        write_reg(ADDR, Register::Config, 0b1111_1011),
+        read_reg(ADDR, Register::OutputPort, 0x00),
+        write_reg(ADDR, Register::OutputPort, 0b0000_0100),
+        read_reg(ADDR, Register::OutputPort, 0b0000_0100),
+        write_reg(ADDR, Register::OutputPort, 0x00),
+        read_reg(ADDR, Register::InputPort, 0xA0),
+    ]);
+    assert_eq!(block_on(blink(&mut tca, 2)).unwrap(), 0xA0);
+    assert!(matches!(
+        block_on(AsyncIoExpander::read_pin_input(&mut tca, 8)),
+        Err(Tca9534Error::Core(Tca9534CoreError::InvalidPin))
+    ));
+    i2c.done();
+}
+
+#[test]
+fn program_reports_config_mismatch() {
+    let (mut tca, mut i2c) = driver(&[
+        write_reg(ADDR, Register::OutputPort, 0x0F),
+        write_reg(ADDR, Register::Polarity, 0x30),
+        write_reg(ADDR, Register::Config, 0xF0),
+        read_reg(ADDR, Register::OutputPort, 0x0F),
+        read_reg(ADDR, Register::Polarity, 0x30),
+        read_reg(ADDR, Register::Config, 0xFF),
+    ]);
+    assert!(matches!(
+        block_on(tca.program(0xF0, 0x30, 0x0F)),
+        Err(Tca9534Error::Core(Tca9534CoreError::VerifyFailed))
+    ));
+    i2c.done();
+}
+
+#[test]
+fn dump_state_flags_output_mismatch() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::InputPort, 0x00),
+        read_reg(ADDR, Register::OutputPort, 0x80),
+        read_reg(ADDR, Register::Polarity, 0x00),
+        read_reg(ADDR, Register::Config, 0x7F),
+    ]);
+    let mut out = String::new();
+    block_on(tca.dump_state(&mut out)).unwrap();
+    assert!(out.ends_with("P7: out out=H in=L MISMATCH\n"));
+    i2c.done();
+}
+
+#[test]
+fn describe_reports_cache_before_read() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::InputPort, 0x00),
+        read_reg(ADDR, Register::OutputPort, 0x80),
+        read_reg(ADDR, Register::Polarity, 0x00),
+        read_reg(ADDR, Register::Config, 0x7F),
+    ]);
+    let description = block_on(tca.describe()).unwrap();
+    assert!(description.initialized);
+    assert_eq!(description.label, None);
+    assert_eq!(description.cache_matches(Register::Config), Some(false));
+    assert_eq!(description.cache_matches(Register::Polarity), Some(true));
+    assert!(description.pin(7).unwrap().mismatch);
+    i2c.done();
+}
+
+#[test]
+fn general_call_reset_sends_reset_byte_to_address_zero() {
+    let mut i2c = Mock::new(&[Transaction::write(0x00, vec![0x06])]);
+    block_on(Tca9534Async::general_call_reset(&mut i2c)).unwrap();
+    i2c.done();
+}
@@ -0,0 +1,101 @@
+#![cfg(feature = "embedded-hal")]
+
+mod common;
+
+use common::*;
+use embedded_hal_mock::eh1::i2c::Mock;
+use tca9534_driver_rs::{
+    tca9534_config, tca9534_pins, InitialConfig, PinLevel, Register, Tca9534Sync,
+};
+
+tca9534_pins! {
+    Board {
+        relay_main: 0 => Output(Low),
+        relay_aux: 1 => Output(High),
+        door_switch: 4 => Input(Inverted),
+        limit_switch: 5 => Input(Normal),
+    }
+}
+
+#[test]
+fn registers_follow_definitions() {
+    assert_eq!(Board::OUTPUT, 0b0000_0010);
+    assert_eq!(Board::POLARITY, 0b0001_0000);
+    assert_eq!(Board::CONFIG, 0b1111_1100);
+    assert_eq!(Board.relay_aux().pin(), 1);
+    assert_eq!(Board.limit_switch().pin(), 5);
+}
+
+#[test]
+fn apply_writes_glitch_free_in_three_writes() {
+    let mut i2c = Mock::new(&after_init(
+        ADDR,
+        &[
+            write_reg(ADDR, Register::OutputPort, 0b0000_0010),
+            write_reg(ADDR, Register::Polarity, 0b0001_0000),
+            write_reg(ADDR, Register::Config, 0b1111_1100),
+        ],
+    ));
+    let mut tca = Tca9534Sync::new(i2c.clone(), ADDR).unwrap();
+    Board.apply(&mut tca).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn typed_accessors_drive_and_sense_pins() {
+    let mut i2c = Mock::new(&after_init(
+        ADDR,
+        &[
+            read_reg(ADDR, Register::OutputPort, 0b0000_0010),
+            write_reg(ADDR, Register::OutputPort, 0b0000_0011),
+            read_reg(ADDR, Register::InputPort, 0b0001_0000),
+            read_reg(ADDR, Register::InputPort, 0b0001_0000),
+        ],
+    ));
+    let mut tca = Tca9534Sync::new(i2c.clone(), ADDR).unwrap();
+    let board = Board;
+    board.relay_main().set_high(&mut tca).unwrap();
+    assert!(board.door_switch().is_active(&mut tca).unwrap());
+    assert!(!board.limit_switch().is_active(&mut tca).unwrap());
+    i2c.done();
+}
+
+#[test]
+fn config_macro_matches_builder() {
+    const SETUP: InitialConfig = tca9534_config!(
+        outputs: [0, 1, 2] = high, [3] = low,
+        inputs: rest,
+        inverted: [4, 5]
+    );
+    const BUILT: InitialConfig = InitialConfig::new()
+        .with_outputs(&[0, 1, 2], PinLevel::High)
+        .with_outputs(&[3], PinLevel::Low)
+        .with_inverted(&[4, 5]);
+    assert_eq!(SETUP.output(), 0b0000_0111);
+    assert_eq!(SETUP.polarity(), 0b0011_0000);
+    assert_eq!(SETUP.config(), 0b1111_0000);
+    assert_eq!(
+        (SETUP.output(), SETUP.polarity(), SETUP.config()),
+        (BUILT.output(), BUILT.polarity(), BUILT.config())
+    );
+
+    let inputs_only = tca9534_config!(inputs: [6, 7], inverted: [7]);
+    assert_eq!(inputs_only.config(), 0xFF);
+    assert_eq!(inputs_only.polarity(), 0x80);
+}
+
+#[test]
+fn apply_initial_config_writes_output_polarity_config() {
+    const SETUP: InitialConfig = tca9534_config!(outputs: [7] = high, inverted: [0]);
+    let mut i2c = Mock::new(&after_init(
+        ADDR,
+        &[
+            write_reg(ADDR, Register::OutputPort, 0x80),
+            write_reg(ADDR, Register::Polarity, 0x01),
+            write_reg(ADDR, Register::Config, 0x7F),
+        ],
+    ));
+    let mut tca = Tca9534Sync::new(i2c.clone(), ADDR).unwrap();
+    tca.apply_initial_config(&SETUP).unwrap();
+    i2c.done();
+}
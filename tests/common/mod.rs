@@ -0,0 +1,227 @@
+//! Shared helpers for the transaction-level driver tests.
+#![allow(dead_code)]
+
+use core::cell::Cell;
+use core::future::Future;
+use core::pin::{pin, Pin};
+use core::task::{Context, Poll, Waker};
+use std::rc::Rc;
+
+use embedded_hal_mock::eh1::i2c::Transaction;
+use tca9534_driver_rs::{defaults, Register, SyncTransport, Tca9534Error, INIT_SEQUENCE};
+
+/// Default device address used by the tests.
+pub const ADDR: u8 = 0x20;
+
+/// Expect a single register read returning `value`.
+pub fn read_reg(addr: u8, reg: Register, value: u8) -> Transaction {
+    Transaction::write_read(addr, vec![reg.addr()], vec![value])
+}
+
+/// Expect a single register write of `value`.
+pub fn write_reg(addr: u8, reg: Register, value: u8) -> Transaction {
+    Transaction::write(addr, vec![reg.addr(), value])
+}
+
+/// Expect the transactions of `init()`, with the inputs reading `input`.
+pub fn init_transactions(addr: u8, input: u8) -> Vec<Transaction> {
+    INIT_SEQUENCE
+        .iter()
+        .map(|&(reg, value)| write_reg(addr, reg, value))
+        .chain([read_reg(addr, Register::InputPort, input)])
+        .collect()
+}
+
+/// Build an expectation list starting with the `init()` transactions.
+pub fn after_init(addr: u8, rest: &[Transaction]) -> Vec<Transaction> {
+    let mut expectations = init_transactions(addr, 0x00);
+    expectations.extend_from_slice(rest);
+    expectations
+}
+
+/// Run a future to completion; the mock transports never return `Pending`.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+/// How the simulated device's bus completes async transfers; sync transfers are immediate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bus {
+    Ready,
+    /// Each transfer returns `Pending` once before taking effect.
+    YieldOnce,
+    /// Each transfer completes after this many milliseconds of the embassy-time clock.
+    Slow(u64),
+    /// Transfers never complete.
+    Wedged,
+}
+
+/// Register file of a simulated device with its INT line, shared between clones.
+///
+/// Starts in the power-on state with all inputs low. Bare reads return the register
+/// the last transfer pointed at, as on the real device.
+#[derive(Clone)]
+pub struct FakeDevice {
+    regs: Rc<Cell<[u8; 4]>>,
+    pointer: Rc<Cell<u8>>,
+    bus: Rc<Cell<Bus>>,
+    int_asserted: Rc<Cell<bool>>,
+    /// Input value applied while the next Input Port read is in progress.
+    during_read: Rc<Cell<Option<u8>>>,
+}
+
+impl FakeDevice {
+    pub fn new() -> Self {
+        let snapshot = defaults::POWER_ON_SNAPSHOT;
+        Self {
+            regs: Rc::new(Cell::new([
+                0x00,
+                snapshot.output,
+                snapshot.polarity,
+                snapshot.config,
+            ])),
+            pointer: Rc::new(Cell::new(0)),
+            bus: Rc::new(Cell::new(Bus::Ready)),
+            int_asserted: Rc::new(Cell::new(false)),
+            during_read: Rc::new(Cell::new(None)),
+        }
+    }
+
+    pub fn reg(&self, reg: Register) -> u8 {
+        self.regs.get()[reg.addr() as usize]
+    }
+
+    /// Set a register behind the driver's back; see `set_input` for the Input Port.
+    pub fn set_reg(&self, reg: Register, value: u8) {
+        let mut regs = self.regs.get();
+        regs[reg.addr() as usize] = value;
+        self.regs.set(regs);
+    }
+
+    pub fn set_bus(&self, bus: Bus) {
+        self.bus.set(bus);
+    }
+
+    /// Drive the input pins, asserting INT if they change.
+    pub fn set_input(&self, value: u8) {
+        if value != self.reg(Register::InputPort) {
+            self.int_asserted.set(true);
+        }
+        self.set_reg(Register::InputPort, value);
+    }
+
+    /// Change the inputs to `value` after the next Input Port read latched its value.
+    pub fn change_input_during_next_read(&self, value: u8) {
+        self.during_read.set(Some(value));
+    }
+
+    pub fn int_asserted(&self) -> bool {
+        self.int_asserted.get()
+    }
+
+    fn write_bytes(&self, bytes: &[u8]) {
+        self.pointer.set(bytes[0]);
+        if let [reg, value, ..] = *bytes {
+            let mut regs = self.regs.get();
+            regs[reg as usize] = value;
+            self.regs.set(regs);
+        }
+    }
+
+    fn read_bytes(&self, rd_bytes: &mut [u8]) {
+        let reg = self.pointer.get();
+        rd_bytes[0] = self.regs.get()[reg as usize];
+        if reg == Register::InputPort.addr() {
+            self.int_asserted.set(false);
+            // The pins change after the value was latched; INT is not asserted again
+            if let Some(next) = self.during_read.take() {
+                self.set_reg(Register::InputPort, next);
+            }
+        }
+    }
+
+    async fn transfer(&self) {
+        match self.bus.get() {
+            Bus::Ready => {}
+            Bus::YieldOnce => YieldOnce(false).await,
+            Bus::Slow(ms) => embassy_time::Timer::after_millis(ms).await,
+            Bus::Wedged => core::future::pending().await,
+        }
+    }
+}
+
+impl SyncTransport for FakeDevice {
+    type Error = Tca9534Error<()>;
+
+    fn write(&mut self, _addr: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.write_bytes(bytes);
+        Ok(())
+    }
+
+    fn read(&mut self, _addr: u8, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        self.read_bytes(bytes);
+        Ok(())
+    }
+
+    fn write_read(
+        &mut self,
+        _addr: u8,
+        wr_bytes: &[u8],
+        rd_bytes: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.write_bytes(wr_bytes);
+        self.read_bytes(rd_bytes);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl tca9534_driver_rs::AsyncTransport for FakeDevice {
+    type Error = Tca9534Error<()>;
+
+    async fn write(&mut self, _addr: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.transfer().await;
+        self.write_bytes(bytes);
+        Ok(())
+    }
+
+    async fn read(&mut self, _addr: u8, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        self.transfer().await;
+        self.read_bytes(bytes);
+        Ok(())
+    }
+
+    async fn write_read(
+        &mut self,
+        _addr: u8,
+        wr_bytes: &[u8],
+        rd_bytes: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.transfer().await;
+        self.write_bytes(wr_bytes);
+        self.read_bytes(rd_bytes);
+        Ok(())
+    }
+}
+
+/// Future returning `Pending` once, waking itself.
+struct YieldOnce(bool);
+
+impl Future for YieldOnce {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+        self.0 = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
@@ -0,0 +1,36 @@
+#![cfg(feature = "defmt")]
+
+use tca9534_driver_rs::{Port, PortBitsDefmt};
+
+fn assert_format<T: defmt::Format>(_: &T) {}
+
+#[test]
+fn port_bits_wrapper_is_defmt_formattable() {
+    let bits = PortBitsDefmt(0b1000_0001);
+    assert_format(&bits);
+    assert_format(&Port(bits.0));
+    assert_eq!(bits.0, 0x81);
+}
+
+#[cfg(feature = "embedded-hal")]
+#[test]
+fn labeled_driver_is_defmt_formattable() {
+    let i2c = embedded_hal_mock::eh1::i2c::Mock::new(&[]);
+    let mut tca =
+        tca9534_driver_rs::Tca9534Sync::new_without_init(i2c.clone(), 0x20).with_label("KEYPAD");
+    assert_format(&tca);
+    tca.set_label("FRONT_PANEL");
+    assert_eq!(tca.label(), Some("FRONT_PANEL"));
+    i2c.clone().done();
+}
+
+#[test]
+fn config_diff_is_defmt_formattable() {
+    let diff = tca9534_driver_rs::ConfigDiff {
+        output: 0x20,
+        polarity: 0x00,
+        config: 0x09,
+    };
+    assert_format(&diff);
+    assert_eq!(diff.pins(), 0x29);
+}
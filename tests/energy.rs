@@ -0,0 +1,107 @@
+#![cfg(all(feature = "energy", feature = "embedded-hal"))]
+
+mod common;
+
+use common::*;
+use embedded_hal::i2c::ErrorKind;
+use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+use tca9534_driver_rs::{ByteCounts, PinLevel, ReadWriteBytes, Register, Tca9534Sync};
+
+#[test]
+fn byte_counts_attribute_traffic_to_registers() {
+    let mut i2c = Mock::new(&after_init(
+        ADDR,
+        &[
+            read_reg(ADDR, Register::OutputPort, 0x00),
+            write_reg(ADDR, Register::OutputPort, 0x01),
+            read_reg(ADDR, Register::InputPort, 0x00),
+            Transaction::read(ADDR, vec![0x04]),
+        ],
+    ));
+    let mut tca = Tca9534Sync::new(i2c.clone(), ADDR).unwrap();
+
+    // init(): three register writes and an Input Port read
+    let counts = tca.byte_counts();
+    assert_eq!(
+        counts.total,
+        ReadWriteBytes {
+            read: 1,
+            written: 7
+        }
+    );
+    assert_eq!(
+        counts.get(Register::Config),
+        ReadWriteBytes {
+            read: 0,
+            written: 2
+        }
+    );
+    tca.reset_byte_counts();
+    assert_eq!(tca.byte_counts(), ByteCounts::default());
+
+    tca.set_pin_output(0, PinLevel::High).unwrap();
+    tca.poll_register_fast(Register::InputPort, 2, |value| value != 0)
+        .unwrap();
+
+    let counts = tca.byte_counts();
+    assert_eq!(
+        counts.per_register,
+        [
+            ReadWriteBytes {
+                read: 2,
+                written: 1
+            },
+            ReadWriteBytes {
+                read: 1,
+                written: 3
+            },
+            ReadWriteBytes::default(),
+            ReadWriteBytes::default(),
+        ]
+    );
+    assert_eq!(
+        counts.total,
+        ReadWriteBytes {
+            read: 3,
+            written: 4
+        }
+    );
+    assert_eq!(counts.total.total(), 7);
+    i2c.done();
+}
+
+#[test]
+fn failed_transfers_are_counted() {
+    let mut i2c = Mock::new(&after_init(
+        ADDR,
+        &[write_reg(ADDR, Register::Config, 0x00).with_error(ErrorKind::Other)],
+    ));
+    let mut tca = Tca9534Sync::new(i2c.clone(), ADDR).unwrap();
+    tca.reset_byte_counts();
+
+    assert!(tca.set_port_config(0x00).is_err());
+    assert_eq!(
+        tca.byte_counts().get(Register::Config),
+        ReadWriteBytes {
+            read: 0,
+            written: 2
+        }
+    );
+    i2c.done();
+}
+
+#[cfg(all(feature = "async", feature = "embedded-hal-async"))]
+#[test]
+fn async_force_writes_are_not_counted() {
+    let mut i2c = Mock::new(&after_init(
+        ADDR,
+        &[write_reg(ADDR, Register::OutputPort, 0xFF)],
+    ));
+    let mut tca = block_on(tca9534_driver_rs::Tca9534Async::new(i2c.clone(), ADDR)).unwrap();
+    tca.reset_byte_counts();
+
+    // The emergency path skips all bookkeeping, byte counting included
+    block_on(tca.force_write_output_port(0xFF)).unwrap();
+    assert_eq!(tca.byte_counts(), ByteCounts::default());
+    i2c.done();
+}
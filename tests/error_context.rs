@@ -0,0 +1,104 @@
+#![cfg(all(feature = "error-context", feature = "embedded-hal"))]
+
+mod common;
+
+use common::*;
+use embedded_hal::i2c::ErrorKind;
+use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+use tca9534_driver_rs::{Direction, FailedAccess, PinLevel, Register, Tca9534Error, Tca9534Sync};
+
+#[test]
+fn failed_read_reports_register_and_direction() {
+    let mut i2c = Mock::new(&after_init(
+        ADDR,
+        &[read_reg(ADDR, Register::OutputPort, 0x00).with_error(ErrorKind::Other)],
+    ));
+    let mut tca = Tca9534Sync::new(i2c.clone(), ADDR).unwrap();
+    assert_eq!(tca.last_failed_access(), None);
+
+    let err = tca.set_pin_output(1, PinLevel::High).unwrap_err();
+    // The error keeps its shape; the context is recorded by the driver
+    assert!(matches!(err, Tca9534Error::I2c(ErrorKind::Other)));
+    let access = tca.last_failed_access().unwrap();
+    assert_eq!(
+        access,
+        FailedAccess {
+            register: Register::OutputPort,
+            direction: Direction::Read,
+        }
+    );
+    assert_eq!(
+        format!("{} failed: {}", access, err),
+        "read of OutputPort failed: I2C error: Other"
+    );
+    i2c.done();
+}
+
+#[test]
+fn failed_write_reports_register_and_direction() {
+    let mut i2c = Mock::new(&after_init(
+        ADDR,
+        &[
+            write_reg(ADDR, Register::Config, 0x0F).with_error(ErrorKind::Other),
+            read_reg(ADDR, Register::InputPort, 0x00),
+        ],
+    ));
+    let mut tca = Tca9534Sync::new(i2c.clone(), ADDR).unwrap();
+    assert!(matches!(
+        tca.set_port_config(0x0F),
+        Err(Tca9534Error::I2c(ErrorKind::Other))
+    ));
+
+    // A later successful transfer keeps the record
+    tca.read_input_port().unwrap();
+    assert_eq!(
+        tca.last_failed_access(),
+        Some(FailedAccess {
+            register: Register::Config,
+            direction: Direction::Write,
+        })
+    );
+    i2c.done();
+}
+
+#[test]
+fn bare_poll_read_reports_polled_register() {
+    let mut i2c = Mock::new(&[
+        read_reg(ADDR, Register::InputPort, 0x00),
+        Transaction::read(ADDR, vec![0x00]).with_error(ErrorKind::Other),
+    ]);
+    let mut tca = Tca9534Sync::new_without_init(i2c.clone(), ADDR);
+    assert!(tca
+        .poll_register_fast(Register::InputPort, 2, |value| value != 0)
+        .is_err());
+    assert_eq!(
+        tca.last_failed_access(),
+        Some(FailedAccess {
+            register: Register::InputPort,
+            direction: Direction::Read,
+        })
+    );
+    i2c.done();
+}
+
+#[cfg(all(feature = "async", feature = "embedded-hal-async"))]
+#[test]
+fn async_failed_access_reports_register() {
+    let mut i2c = Mock::new(&after_init(
+        ADDR,
+        &[read_reg(ADDR, Register::Polarity, 0x00).with_error(ErrorKind::Other)],
+    ));
+    let mut tca = block_on(tca9534_driver_rs::Tca9534Async::new(i2c.clone(), ADDR)).unwrap();
+    assert!(matches!(
+        block_on(tca.read_port_polarity()),
+        Err(Tca9534Error::I2c(ErrorKind::Other))
+    ));
+    assert_eq!(
+        tca.last_failed_access(),
+        Some(FailedAccess {
+            register: Register::Polarity,
+            direction: Direction::Read,
+        })
+    );
+    i2c.done();
+}
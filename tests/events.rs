@@ -0,0 +1,46 @@
+use tca9534_driver_rs::{EdgeDetector, PinEvent, PinEvents};
+
+fn collect(events: PinEvents) -> Vec<PinEvent> {
+    events.iter().copied().collect()
+}
+
+#[test]
+fn first_sample_sets_baseline() {
+    let mut edges = EdgeDetector::new();
+    assert!(edges.update(0xA5).is_empty());
+    assert_eq!(edges.last(), Some(0xA5));
+    assert!(edges.update(0xA5).is_empty());
+}
+
+#[test]
+fn multi_bit_change_reports_each_edge_in_pin_order() {
+    let mut edges = EdgeDetector::with_baseline(0b1001_0001);
+    let events = edges.update(0b0101_0100);
+    assert_eq!(events.len(), 4);
+    assert_eq!(
+        collect(events),
+        [
+            PinEvent::FallingEdge(0),
+            PinEvent::RisingEdge(2),
+            PinEvent::RisingEdge(6),
+            PinEvent::FallingEdge(7),
+        ]
+    );
+
+    // Events are relative to the previous sample, not the baseline
+    assert_eq!(
+        collect(edges.update(0b0101_0000)),
+        [PinEvent::FallingEdge(2)]
+    );
+}
+
+#[test]
+fn all_pins_toggling_fills_queue() {
+    let mut edges = EdgeDetector::with_baseline(0x00);
+    let events = edges.update(0xFF);
+    assert_eq!(events.len(), PinEvents::CAPACITY);
+    assert!(events
+        .iter()
+        .enumerate()
+        .all(|(pin, event)| *event == PinEvent::RisingEdge(pin as u8)));
+}
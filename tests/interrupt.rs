@@ -0,0 +1,73 @@
+mod common;
+
+use common::*;
+use tca9534_driver_rs::{InputChanges, Tca9534Sync};
+
+#[test]
+fn change_during_interrupt_read_is_reported_by_reread() {
+    let device = FakeDevice::new();
+    let mut tca = Tca9534Sync::new(device.clone(), ADDR).unwrap();
+
+    // Pin 0 rises and asserts INT; pin 1 rises while the service read is in progress
+    device.set_input(0b0000_0001);
+    device.change_input_during_next_read(0b0000_0011);
+    assert!(device.int_asserted());
+
+    let changes = tca.service_interrupt().unwrap();
+    assert_eq!(
+        changes,
+        InputChanges {
+            previous: 0x00,
+            current: 0b0000_0011,
+            changed: 0b0000_0011,
+        }
+    );
+    assert_eq!(changes.rising(), 0b0000_0011);
+    assert!(!device.int_asserted());
+
+    // Nothing is left over for the next call
+    assert!(tca.service_interrupt().unwrap().is_empty());
+}
+
+#[test]
+fn without_reread_change_during_read_is_lost_until_next_interrupt() {
+    let device = FakeDevice::new();
+    let mut tca = Tca9534Sync::new(device.clone(), ADDR).unwrap();
+    tca.set_interrupt_rereads(0);
+
+    device.set_input(0b0000_0001);
+    device.change_input_during_next_read(0b0000_0011);
+
+    let changes = tca.service_interrupt().unwrap();
+    assert_eq!(changes.changed, 0b0000_0001);
+    // INT stays deasserted, so nothing prompts another service call
+    assert!(!device.int_asserted());
+}
+
+#[test]
+fn pin_toggling_back_between_reads_is_reported() {
+    let device = FakeDevice::new();
+    let mut tca = Tca9534Sync::new(device.clone(), ADDR).unwrap();
+
+    device.set_input(0b1000_0000);
+    device.change_input_during_next_read(0x00);
+
+    let changes = tca.service_interrupt().unwrap();
+    assert_eq!(changes.changed, 0b1000_0000);
+    assert_eq!(changes.current, 0x00);
+    assert_eq!(changes.falling(), 0b1000_0000);
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_change_during_interrupt_read_is_reported_by_reread() {
+    let device = FakeDevice::new();
+    let mut tca = block_on(tca9534_driver_rs::Tca9534Async::new(device.clone(), ADDR)).unwrap();
+
+    device.set_input(0b0000_0100);
+    device.change_input_during_next_read(0b0000_1100);
+
+    let changes = block_on(tca.service_interrupt()).unwrap();
+    assert_eq!(changes.changed, 0b0000_1100);
+    assert_eq!(changes.current, 0b0000_1100);
+}
@@ -0,0 +1,79 @@
+#![cfg(feature = "lcd")]
+
+mod common;
+
+use common::*;
+use embedded_hal_mock::eh1::delay::NoopDelay;
+use embedded_hal_mock::eh1::i2c::Mock;
+use tca9534_driver_rs::{
+    Hd44780Bus, LcdPins, Register, Tca9534CoreError, Tca9534Error, Tca9534Sync,
+};
+
+/// Common backpack wiring: RS=P0, E=P2, backlight=P3, D4-D7=P4-P7 (P1 left free).
+const PINS: LcdPins = LcdPins {
+    rs: 0,
+    e: 2,
+    data: [4, 5, 6, 7],
+    backlight: Some(3),
+};
+
+#[test]
+fn init_and_print_hi_sends_expected_output_sequence() {
+    let mut expectations = after_init(
+        ADDR,
+        &[
+            read_reg(ADDR, Register::OutputPort, 0b0000_0010),
+            write_reg(ADDR, Register::OutputPort, 0b0000_0010),
+            read_reg(ADDR, Register::Config, 0xFF),
+            write_reg(ADDR, Register::Config, 0b0000_0010),
+        ],
+    );
+    // Backlight on, then each nibble as (setup,) E high, E low; P1 stays high
+    #[rustfmt::skip]
+    let outputs: &[u8] = &[
+        0x0A,
+        // 4-bit initialization by instruction
+        0x3A, 0x3E, 0x3A, 0x3E, 0x3A, 0x3E, 0x3A, 0x2A, 0x2E, 0x2A,
+        // function set 0x28, display on 0x0C, clear 0x01, entry mode 0x06
+        0x2E, 0x2A, 0x8A, 0x8E, 0x8A,
+        0x0A, 0x0E, 0x0A, 0xCA, 0xCE, 0xCA,
+        0x0A, 0x0E, 0x0A, 0x1A, 0x1E, 0x1A,
+        0x0A, 0x0E, 0x0A, 0x6A, 0x6E, 0x6A,
+        // 'H' (0x48) and 'i' (0x69) with RS high
+        0x4B, 0x4F, 0x4B, 0x8B, 0x8F, 0x8B,
+        0x6B, 0x6F, 0x6B, 0x9B, 0x9F, 0x9B,
+    ];
+    expectations.extend(
+        outputs
+            .iter()
+            .map(|&value| write_reg(ADDR, Register::OutputPort, value)),
+    );
+
+    let mut i2c = Mock::new(&expectations);
+    let tca = Tca9534Sync::new(i2c.clone(), ADDR).unwrap();
+    let mut lcd = Hd44780Bus::new(tca, PINS).map_err(|(_, e)| e).unwrap();
+    lcd.set_backlight(true).unwrap();
+    lcd.init(&mut NoopDelay).unwrap();
+    lcd.write_str("Hi", &mut NoopDelay).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn new_rejects_shared_pins() {
+    let mut i2c = Mock::new(&init_transactions(ADDR, 0x00));
+    let tca = Tca9534Sync::new(i2c.clone(), ADDR).unwrap();
+    let pins = LcdPins {
+        backlight: Some(0),
+        ..PINS
+    };
+    let Err((tca, error)) = Hd44780Bus::new(tca, pins) else {
+        panic!("shared pins accepted");
+    };
+    assert!(matches!(
+        error,
+        Tca9534Error::Core(Tca9534CoreError::InvalidArgument)
+    ));
+    // The driver is handed back untouched
+    assert_eq!(tca.cached_output(), Some(0x00));
+    i2c.done();
+}
@@ -0,0 +1,63 @@
+#![cfg(feature = "embassy-time")]
+
+mod common;
+
+use core::future::Future;
+use core::pin::pin;
+use core::task::{Context, Poll, Waker};
+
+use common::*;
+use embassy_time::{Duration, MockDriver};
+use tca9534_driver_rs::{
+    Deadline, PinLevel, Register, Tca9534Async, Tca9534CoreError, Tca9534Error,
+};
+
+/// Poll a future, advancing the mock clock by `step_ms` after each pending poll.
+fn run_with_clock<F: Future>(future: F, step_ms: u64, steps: usize) -> Poll<F::Output> {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    for _ in 0..steps {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return Poll::Ready(output);
+        }
+        MockDriver::get().advance(Duration::from_millis(step_ms));
+    }
+    future.as_mut().poll(&mut cx)
+}
+
+// The mock clock is global, so the scenarios share one test to run in sequence.
+#[test]
+fn operation_timeout_bounds_each_transaction() {
+    let device = FakeDevice::new();
+    device.set_input(0x5A);
+    device.set_reg(Register::OutputPort, 0x00);
+    let mut tca = Tca9534Async::new_without_init(Deadline::new(device.clone()), ADDR);
+
+    // Without a timeout a wedged bus stays pending
+    device.set_bus(Bus::Wedged);
+    assert!(run_with_clock(tca.read_input_port(), 100, 10).is_pending());
+
+    // With a timeout it fails once the deadline passes
+    tca.set_operation_timeout(Some(Duration::from_millis(10)));
+    assert_eq!(tca.operation_timeout(), Some(Duration::from_millis(10)));
+    let result = run_with_clock(tca.read_input_port(), 5, 2);
+    assert!(matches!(
+        result,
+        Poll::Ready(Err(Tca9534Error::Core(Tca9534CoreError::Timeout)))
+    ));
+
+    // The driver remains usable once the bus recovers
+    device.set_bus(Bus::Ready);
+    assert_eq!(block_on(tca.read_input_port()).unwrap(), 0x5A);
+
+    // Read-modify-write gets the full timeout per transaction
+    device.set_bus(Bus::Slow(6));
+    let result = run_with_clock(tca.set_pin_output(3, PinLevel::High), 6, 2);
+    assert!(matches!(result, Poll::Ready(Ok(()))));
+    assert_eq!(device.reg(Register::OutputPort), 0x08);
+
+    // `None` restores unbounded waiting
+    tca.set_operation_timeout(None);
+    device.set_bus(Bus::Wedged);
+    assert!(run_with_clock(tca.read_input_port(), 100, 10).is_pending());
+}
@@ -0,0 +1,35 @@
+use tca9534_driver_rs::Port;
+
+#[test]
+fn bit_operators_compose_masks() {
+    let low = Port::pin(0) | Port::pin(1);
+    assert_eq!(low, Port(0b0000_0011));
+    assert_eq!(low & Port(0b0000_0110), Port::pin(1));
+    assert_eq!(low ^ Port::ALL, Port(0b1111_1100));
+    assert_eq!(!low, Port(0b1111_1100));
+
+    let mut mask = Port::NONE;
+    mask |= Port::pin(7);
+    mask &= Port::ALL;
+    assert_eq!(mask.bits(), 0x80);
+}
+
+#[test]
+fn shifts_saturate_past_port_width() {
+    assert_eq!(Port::pin(0) << 3, Port::pin(3));
+    assert_eq!(Port::pin(7) >> 7, Port::pin(0));
+    assert_eq!(Port::pin(7) << 1, Port::NONE);
+    assert_eq!(Port::ALL << 8, Port::NONE);
+    assert_eq!(Port::ALL >> 9, Port::NONE);
+}
+
+#[test]
+fn pins_lists_members_in_order() {
+    let port = Port(0b1000_0101);
+    assert!(port.contains(2));
+    assert!(!port.contains(1));
+    assert!(!port.contains(8));
+    assert!(port.pins().eq([0, 2, 7]));
+    assert_eq!(u8::from(port), 0b1000_0101);
+    assert!(Port::from(0).is_empty());
+}
@@ -0,0 +1,79 @@
+use tca9534_driver_rs::{
+    PinConfig, PinLevel, PinPolarity, Register, Tca9534CoreError, REGISTER_ADDRS,
+};
+
+#[test]
+fn try_from_decodes_bit_values() {
+    assert_eq!(PinLevel::try_from(0), Ok(PinLevel::Low));
+    assert_eq!(PinLevel::try_from(1), Ok(PinLevel::High));
+    assert_eq!(PinConfig::try_from(0), Ok(PinConfig::Output));
+    assert_eq!(PinConfig::try_from(1), Ok(PinConfig::Input));
+    assert_eq!(PinPolarity::try_from(0), Ok(PinPolarity::Normal));
+    assert_eq!(PinPolarity::try_from(1), Ok(PinPolarity::Inverted));
+
+    for level in [PinLevel::Low, PinLevel::High] {
+        assert_eq!(PinLevel::try_from(level.bits()), Ok(level));
+    }
+}
+
+#[test]
+fn from_port_reads_the_pin_bit() {
+    assert_eq!(PinLevel::from_port(0x80, 7), PinLevel::High);
+    assert_eq!(PinLevel::from_port(0x80, 6), PinLevel::Low);
+    assert_eq!(PinLevel::from_port(0x01, 0), PinLevel::High);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "invalid pin number")]
+fn from_port_rejects_pins_above_7_in_debug_builds() {
+    PinLevel::from_port(0x01, 8);
+}
+
+#[test]
+fn try_from_rejects_out_of_range_values() {
+    assert_eq!(
+        PinLevel::try_from(2),
+        Err(Tca9534CoreError::InvalidArgument)
+    );
+    assert_eq!(
+        PinConfig::try_from(0xFF),
+        Err(Tca9534CoreError::InvalidArgument)
+    );
+    assert_eq!(
+        PinPolarity::try_from(7),
+        Err(Tca9534CoreError::InvalidArgument)
+    );
+}
+
+#[test]
+fn register_addrs_match_register_addresses() {
+    let registers = [
+        Register::InputPort,
+        Register::OutputPort,
+        Register::Polarity,
+        Register::Config,
+    ];
+    for (ordinal, reg) in registers.into_iter().enumerate() {
+        assert_eq!(REGISTER_ADDRS[ordinal], reg.addr());
+        assert_eq!(REGISTER_ADDRS[ordinal], ordinal as u8);
+        assert_eq!(Register::from_addr(REGISTER_ADDRS[ordinal]), Some(reg));
+    }
+}
+
+#[test]
+fn next_walks_registers_in_address_order() {
+    let walked: Vec<Register> =
+        core::iter::successors(Some(Register::InputPort), |reg| reg.next()).collect();
+    assert_eq!(
+        walked,
+        [
+            Register::InputPort,
+            Register::OutputPort,
+            Register::Polarity,
+            Register::Config,
+        ]
+    );
+    assert_eq!(Register::Polarity.next(), Some(Register::Config));
+    assert_eq!(Register::Config.next(), None);
+}
@@ -0,0 +1,38 @@
+#![cfg(feature = "serde")]
+
+use std::collections::HashSet;
+
+use tca9534_driver_rs::{RegisterSnapshot, StateDump};
+
+const SNAPSHOT: RegisterSnapshot = RegisterSnapshot {
+    input: 0x1E,
+    output: 0x0F,
+    polarity: 0x30,
+    config: 0xF0,
+};
+
+#[test]
+fn snapshots_deduplicate_in_a_set() {
+    let mut seen = HashSet::new();
+    assert!(seen.insert(SNAPSHOT));
+    assert!(!seen.insert(SNAPSHOT));
+    assert!(seen.insert(RegisterSnapshot {
+        input: 0x1F,
+        ..SNAPSHOT
+    }));
+    assert_eq!(seen.len(), 2);
+}
+
+#[test]
+fn state_dump_round_trips_through_json() {
+    let dump = StateDump {
+        address: 0x20,
+        registers: SNAPSHOT,
+    };
+    let json = serde_json::to_string(&dump).unwrap();
+    assert_eq!(
+        json,
+        r#"{"address":32,"registers":{"input":30,"output":15,"polarity":48,"config":240}}"#
+    );
+    assert_eq!(serde_json::from_str::<StateDump>(&json).unwrap(), dump);
+}
@@ -0,0 +1,2014 @@
+#![cfg(feature = "embedded-hal")]
+
+mod common;
+
+use common::*;
+use embedded_hal::i2c::ErrorKind;
+use embedded_hal_mock::eh1::delay::{CheckedDelay, NoopDelay, Transaction as Delay};
+use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+use tca9534_driver_rs::{
+    addresses, config, defaults, AppliedReport, ConfigDiff, DegradedPolicy, DeviceDescription,
+    DumpError, Family, InitMode, InitialConfig, LogicalLevel, PinConfig, PinDescription, PinLevel,
+    PinMap, PinPolarity, Port, Register, RegisterSnapshot, SegMap, StableReadError, StaggerOrder,
+    StartupOptions, StateDump, Tca9534CoreError, Tca9534Error, Tca9534Sync, Tca9534SyncAt,
+    INIT_SEQUENCE, WALKING_PATTERN,
+};
+
+fn driver(rest: &[Transaction]) -> (Tca9534Sync<Mock>, Mock) {
+    let i2c = Mock::new(&after_init(ADDR, rest));
+    let tca = Tca9534Sync::new(i2c.clone(), ADDR).unwrap();
+    (tca, i2c)
+}
+
+#[test]
+fn new_writes_init_sequence() {
+    let (tca, mut i2c) = driver(&[]);
+    assert_eq!(tca.address(), ADDR);
+    i2c.done();
+}
+
+#[test]
+fn init_sequence_configures_inputs_before_outputs() {
+    let regs: Vec<Register> = INIT_SEQUENCE.iter().map(|&(reg, _)| reg).collect();
+    assert_eq!(
+        regs,
+        [Register::Config, Register::OutputPort, Register::Polarity]
+    );
+}
+
+#[test]
+fn with_default_address_uses_addr_000() {
+    let mut i2c = Mock::new(&init_transactions(addresses::ADDR_000, 0x00));
+    let tca = Tca9534Sync::with_default_address(i2c.clone()).unwrap();
+    assert_eq!(tca.address(), addresses::ADDR_000);
+    i2c.done();
+}
+
+#[test]
+fn with_address_chains_and_applies_address() {
+    let mut i2c = Mock::new(&[write_reg(0x23, Register::OutputPort, 0x0F)]);
+    let mut tca = Tca9534Sync::new_without_init(i2c.clone(), ADDR)
+        .with_address(0x23)
+        .unwrap();
+    assert_eq!(tca.address(), 0x23);
+    tca.write_output_port(0x0F).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn with_address_hands_driver_back_on_invalid_address() {
+    let mut i2c = Mock::new(&[write_reg(ADDR, Register::OutputPort, 0x0F)]);
+    let Err((mut tca, error)) = Tca9534Sync::new_without_init(i2c.clone(), ADDR).with_address(0x50)
+    else {
+        panic!("0x50 is outside both address windows");
+    };
+    assert_eq!(error, Tca9534CoreError::InvalidAddress);
+    assert_eq!(tca.address(), ADDR);
+    tca.write_output_port(0x0F).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn debug_output_shows_hex_address() {
+    let mut i2c = Mock::new(&[]);
+    let tca = Tca9534Sync::new_without_init(i2c.clone(), 0x3A);
+    let text = format!("{:?}", tca);
+    assert!(text.contains("address: 0x3a"), "{}", text);
+    assert!(text.contains("initialized: false"), "{}", text);
+    // Settings and bookkeeping are left out
+    assert!(!text.contains("strict_init"), "{}", text);
+    assert!(text.ends_with(", .. }"), "{}", text);
+    i2c.done();
+}
+
+#[test]
+fn debug_output_decodes_cached_pins() {
+    let (mut tca, mut i2c) = driver(&[
+        write_reg(ADDR, Register::OutputPort, 0x01),
+        write_reg(ADDR, Register::Polarity, 0x80),
+        write_reg(ADDR, Register::Config, 0xFC),
+    ]);
+    tca.write_output_port(0x01).unwrap();
+    tca.set_port_polarity(0x80).unwrap();
+    tca.set_port_config(0xFC).unwrap();
+    let text = format!("{:?}", tca);
+    assert!(
+        text.contains("pins: [P0: out=H, P1: out=L, P2: in, ") && text.contains("P7: in inverted]"),
+        "{}",
+        text
+    );
+    assert!(!text.contains("written"), "{}", text);
+    assert!(!text.contains("desired_"), "{}", text);
+
+    tca.mark_caches_stale();
+    let text = format!("{:?}", tca);
+    assert!(text.contains("P0: ? out=? polarity=?"), "{}", text);
+    i2c.done();
+}
+
+#[test]
+fn debug_output_shows_label() {
+    let mut i2c = Mock::new(&[]);
+    let tca = Tca9534Sync::new_without_init(i2c.clone(), ADDR).with_label("FRONT_PANEL");
+    assert_eq!(tca.label(), Some("FRONT_PANEL"));
+    let text = format!("{:?}", tca);
+    assert!(text.contains("label: Some(\"FRONT_PANEL\")"), "{}", text);
+    i2c.done();
+}
+
+#[test]
+fn typed_address_driver_offers_driver_methods() {
+    let mut i2c = Mock::new(&[
+        write_reg(0x21, Register::OutputPort, 0x0F),
+        read_reg(0x21, Register::InputPort, 0x80),
+    ]);
+    let mut typed = Tca9534SyncAt::<_, 0x21>::new_without_init(i2c.clone()).with_label("KEYPAD");
+    typed.write_output_port(0x0F).unwrap();
+    assert_eq!(typed.read_pin_input(7).unwrap(), PinLevel::High);
+    assert_eq!(typed.cached_output(), Some(0x0F));
+    assert_eq!(typed.label(), Some("KEYPAD"));
+    i2c.done();
+}
+
+#[test]
+fn typed_address_driver_has_the_address_taking_constructors() {
+    let mut i2c = Mock::new(&init_transactions(0x21, 0x00));
+    let typed = Tca9534SyncAt::<_, 0x21>::new_with_usable_pins(i2c.clone(), 0x0F).unwrap();
+    assert_eq!(typed.address(), 0x21);
+    assert_eq!(typed.usable_pins(), 0x0F);
+    i2c.done();
+}
+
+#[test]
+fn typed_address_driver_converts_to_and_from_runtime_address() {
+    let mut i2c = Mock::new(&init_transactions(0x20, 0x00));
+    let typed = Tca9534SyncAt::<_, 0x20>::new(i2c.clone()).unwrap();
+    assert_eq!(typed.address(), 0x20);
+    let text = format!("{:?}", typed);
+    assert!(
+        text.starts_with("Tca9534SyncAt<0x20>(Tca9534Sync {"),
+        "{}",
+        text
+    );
+
+    let runtime: Tca9534Sync<Mock> = typed.into();
+    let runtime = Tca9534SyncAt::<_, 0x21>::try_from(runtime).unwrap_err();
+    assert!(Tca9534SyncAt::<_, 0x20>::try_from(runtime).is_ok());
+    i2c.done();
+}
+
+#[test]
+fn set_address_forgets_desired_values() {
+    let (mut tca, mut i2c) = driver(&[]);
+    tca.declare_pin_active_level(0, PinLevel::Low).unwrap();
+    tca.set_address(0x21).unwrap();
+    assert!(!tca.is_initialized());
+    assert_eq!(tca.pin_active_level(0).unwrap(), PinLevel::High);
+    assert!(matches!(
+        tca.reassert_config(),
+        Err(Tca9534Error::Core(Tca9534CoreError::NothingToReassert))
+    ));
+    i2c.done();
+}
+
+#[test]
+fn set_address_rejects_address_outside_known_windows() {
+    let (mut tca, mut i2c) = driver(&[]);
+    assert_eq!(tca.set_address(0x50), Err(Tca9534CoreError::InvalidAddress));
+    assert_eq!(tca.address(), ADDR);
+    assert!(tca.is_initialized());
+    i2c.done();
+}
+
+#[test]
+fn switch_device_does_not_reuse_previous_device_state() {
+    let (mut tca, mut i2c) = driver(&[
+        write_reg(ADDR, Register::OutputPort, 0xAA),
+        // Switch reads the new device
+        read_reg(0x21, Register::InputPort, 0x00),
+        read_reg(0x21, Register::OutputPort, 0x0F),
+        read_reg(0x21, Register::Polarity, 0x00),
+        read_reg(0x21, Register::Config, 0xF0),
+        write_reg(0x21, Register::OutputPort, 0x0F),
+    ]);
+    tca.write_output_port(0xAA).unwrap();
+    tca.switch_device(0x21).unwrap();
+    assert_eq!(tca.address(), 0x21);
+    tca.reassert_outputs().unwrap();
+    i2c.done();
+}
+
+#[test]
+fn with_family_default_address_uses_high_window_for_a_variant() {
+    assert_eq!(Family::Tca9534A.default_address(), 0x38);
+    let mut i2c = Mock::new(&init_transactions(0x38, 0x00));
+    let tca = Tca9534Sync::with_family_default_address(i2c.clone(), Family::Tca9534A).unwrap();
+    assert_eq!(tca.address(), 0x38);
+    i2c.done();
+}
+
+#[test]
+fn new_with_por_output_keeps_output_high() {
+    let expectations = [
+        write_reg(ADDR, Register::Config, 0xFF),
+        write_reg(ADDR, Register::OutputPort, config::POR_OUTPUT_DEFAULT),
+        write_reg(ADDR, Register::Polarity, 0x00),
+        read_reg(ADDR, Register::InputPort, 0x00),
+    ];
+    let mut i2c = Mock::new(&expectations);
+    Tca9534Sync::new_with_por_output(i2c.clone(), ADDR).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn new_minimal_skips_registers_already_at_init_values() {
+    let expectations = [
+        read_reg(ADDR, Register::Config, 0xFF),
+        read_reg(ADDR, Register::OutputPort, 0x00),
+        read_reg(ADDR, Register::Polarity, 0x00),
+        read_reg(ADDR, Register::InputPort, 0x00),
+    ];
+    let mut i2c = Mock::new(&expectations);
+    let tca = Tca9534Sync::new_minimal(i2c.clone(), ADDR).unwrap();
+    assert!(tca.is_initialized());
+    i2c.done();
+}
+
+#[test]
+fn new_minimal_writes_only_differing_registers() {
+    let snapshot = defaults::POWER_ON_SNAPSHOT;
+    let expectations = [
+        read_reg(ADDR, Register::Config, snapshot.config),
+        read_reg(ADDR, Register::OutputPort, snapshot.output),
+        write_reg(ADDR, Register::OutputPort, 0x00),
+        read_reg(ADDR, Register::Polarity, snapshot.polarity),
+        read_reg(ADDR, Register::InputPort, 0x00),
+    ];
+    let mut i2c = Mock::new(&expectations);
+    Tca9534Sync::new_minimal(i2c.clone(), ADDR).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn minimal_traffic_init_writes_only_output_and_seeds_caches() {
+    let mut i2c = Mock::new(&[
+        write_reg(ADDR, Register::OutputPort, 0x00),
+        read_reg(ADDR, Register::InputPort, 0x00),
+        // reassert_config rewrites the assumed power-on Config value
+        write_reg(ADDR, Register::Config, 0xFF),
+    ]);
+    let mut tca = Tca9534Sync::new_with_mode(i2c.clone(), ADDR, InitMode::MinimalTraffic).unwrap();
+    assert!(tca.is_initialized());
+    tca.reassert_config().unwrap();
+    i2c.done();
+}
+
+#[test]
+fn minimal_traffic_initial_config_skips_power_on_values() {
+    let cases: [(InitialConfig, Vec<Transaction>); 3] = [
+        (
+            InitialConfig::new(),
+            vec![write_reg(ADDR, Register::OutputPort, 0x00)],
+        ),
+        // Output Port and Polarity match their defaults, only Config differs
+        (
+            InitialConfig::new().with_outputs(&[0, 1, 2, 3, 4, 5, 6, 7], PinLevel::High),
+            vec![write_reg(ADDR, Register::Config, 0x00)],
+        ),
+        (
+            InitialConfig::new().with_inverted(&[7]),
+            vec![
+                write_reg(ADDR, Register::OutputPort, 0x00),
+                write_reg(ADDR, Register::Polarity, 0x80),
+            ],
+        ),
+    ];
+    for (initial, expectations) in cases {
+        let mut i2c = Mock::new(&expectations);
+        let mut tca = Tca9534Sync::new_without_init(i2c.clone(), ADDR);
+        tca.apply_initial_config_with_mode(&initial, InitMode::MinimalTraffic)
+            .unwrap();
+        i2c.done();
+    }
+}
+
+#[test]
+fn full_mode_initial_config_writes_every_register() {
+    let mut i2c = Mock::new(&[
+        write_reg(ADDR, Register::OutputPort, 0x00),
+        write_reg(ADDR, Register::Polarity, 0x00),
+        write_reg(ADDR, Register::Config, 0xFF),
+    ]);
+    let mut tca = Tca9534Sync::new_without_init(i2c.clone(), ADDR);
+    tca.apply_initial_config_with_mode(&InitialConfig::new(), InitMode::Full)
+        .unwrap();
+    i2c.done();
+}
+
+#[test]
+fn apply_config_matching_device_state_writes_nothing() {
+    let (mut tca, mut i2c) = driver(&[]);
+    let report = tca.apply_config(&InitialConfig::new()).unwrap();
+    assert!(report.is_empty());
+    i2c.done();
+}
+
+#[test]
+fn apply_config_writes_changed_registers_in_glitch_free_order() {
+    let (mut tca, mut i2c) = driver(&[
+        write_reg(ADDR, Register::OutputPort, 0b0000_0011),
+        write_reg(ADDR, Register::Polarity, 0b0001_0000),
+        write_reg(ADDR, Register::Config, 0b1111_1100),
+    ]);
+    let cfg = InitialConfig::new()
+        .with_outputs(&[0, 1], PinLevel::High)
+        .with_inverted(&[4]);
+    let report = tca.apply_config(&cfg).unwrap();
+    assert_eq!(
+        report,
+        AppliedReport {
+            output: true,
+            polarity: true,
+            config: true,
+        }
+    );
+    assert_eq!(report.count(), 3);
+    i2c.done();
+}
+
+#[test]
+fn apply_config_output_only_change_writes_one_register() {
+    let (mut tca, mut i2c) = driver(&[
+        write_reg(ADDR, Register::OutputPort, 0b0000_0001),
+        write_reg(ADDR, Register::Config, 0b1111_1110),
+        write_reg(ADDR, Register::OutputPort, 0b0000_0000),
+    ]);
+    tca.apply_config(&InitialConfig::new().with_outputs(&[0], PinLevel::High))
+        .unwrap();
+    let report = tca
+        .apply_config(&InitialConfig::new().with_outputs(&[0], PinLevel::Low))
+        .unwrap();
+    assert_eq!(
+        report,
+        AppliedReport {
+            output: true,
+            ..AppliedReport::default()
+        }
+    );
+    i2c.done();
+}
+
+fn startup_options() -> StartupOptions {
+    StartupOptions {
+        settle_us: 1_000,
+        init_attempts: 3,
+        attempt_delay_us: 500,
+    }
+}
+
+#[test]
+fn new_with_startup_retries_until_device_answers() {
+    let nack = write_reg(ADDR, Register::Config, 0xFF).with_error(ErrorKind::Other);
+    let mut expectations = vec![nack.clone(), nack];
+    expectations.extend(init_transactions(ADDR, 0x00));
+    let mut i2c = Mock::new(&expectations);
+    let mut delay = CheckedDelay::new(&[
+        Delay::delay_us(1_000),
+        Delay::delay_us(500),
+        Delay::delay_us(500),
+    ]);
+    let tca =
+        Tca9534Sync::new_with_startup(i2c.clone(), ADDR, startup_options(), &mut delay).unwrap();
+    assert!(tca.is_initialized());
+    delay.done();
+    i2c.done();
+}
+
+#[test]
+fn new_with_startup_returns_transport_after_last_attempt() {
+    let nack = write_reg(ADDR, Register::Config, 0xFF).with_error(ErrorKind::Other);
+    let i2c = Mock::new(&[nack.clone(), nack.clone(), nack]);
+    let options = startup_options();
+    let result = Tca9534Sync::new_with_startup(i2c, ADDR, options, &mut NoopDelay);
+    let Err((mut transport, error)) = result else {
+        panic!("startup should fail");
+    };
+    assert!(matches!(error, Tca9534Error::I2c(ErrorKind::Other)));
+    transport.done();
+}
+
+#[test]
+fn new_with_pin_table_writes_output_before_config() {
+    use PinConfig::{Input, Output};
+    use PinLevel::{High, Low};
+    let table = [
+        (Output, High),
+        (Output, Low),
+        (Input, Low),
+        (Output, High),
+        (Input, Low),
+        (Input, High),
+        (Output, Low),
+        (Input, Low),
+    ];
+    let initial = InitialConfig::from_pin_table(&table);
+    assert_eq!(initial.output(), 0b0010_1001);
+    assert_eq!(initial.config(), 0b1011_0100);
+    assert_eq!(initial.polarity(), 0x00);
+
+    let mut i2c = Mock::new(&[
+        write_reg(ADDR, Register::OutputPort, 0b0010_1001),
+        write_reg(ADDR, Register::Polarity, 0x00),
+        write_reg(ADDR, Register::Config, 0b1011_0100),
+        read_reg(ADDR, Register::InputPort, 0x00),
+    ]);
+    let tca = Tca9534Sync::new_with_pin_table(i2c.clone(), ADDR, &table).unwrap();
+    assert!(tca.is_initialized());
+    i2c.done();
+}
+
+#[test]
+fn new_with_config_writes_output_before_config() {
+    let initial = InitialConfig::new()
+        .with_outputs(&[0, 1], PinLevel::High)
+        .with_inverted(&[6]);
+    let mut i2c = Mock::new(&[
+        write_reg(ADDR, Register::OutputPort, 0x03),
+        write_reg(ADDR, Register::Polarity, 0x40),
+        write_reg(ADDR, Register::Config, 0xFC),
+        read_reg(ADDR, Register::InputPort, 0x00),
+    ]);
+    let tca = Tca9534Sync::new_with_config(i2c.clone(), ADDR, &initial).unwrap();
+    assert_eq!(tca.cached_config(), Some(0xFC));
+    i2c.done();
+}
+
+#[test]
+fn capture_preinit_state_reads_without_writing() {
+    // The mock fails on any write during the capture
+    let mut expectations = snapshot_reads(0x5A, 0x81, 0x00, 0x7E);
+    expectations.extend(init_transactions(ADDR, 0x5A));
+    let mut i2c = Mock::new(&expectations);
+
+    let mut transport = i2c.clone();
+    let captured = Tca9534Sync::capture_preinit_state(&mut transport, ADDR).unwrap();
+    assert_eq!(
+        captured,
+        RegisterSnapshot {
+            input: 0x5A,
+            output: 0x81,
+            polarity: 0x00,
+            config: 0x7E,
+        }
+    );
+
+    // Normal initialization proceeds afterwards
+    Tca9534Sync::new(transport, ADDR).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn new_without_init_does_not_touch_the_device() {
+    let mut i2c = Mock::new(&[]);
+    let tca = Tca9534Sync::new_without_init(i2c.clone(), ADDR);
+    assert_eq!(tca.address(), ADDR);
+    i2c.done();
+}
+
+#[test]
+fn init_failure_is_reported() {
+    let mut i2c = Mock::new(&[
+        Transaction::write(ADDR, vec![Register::Config.addr(), 0xFF]).with_error(ErrorKind::Other),
+    ]);
+    let result = Tca9534Sync::new(i2c.clone(), ADDR);
+    assert!(matches!(result, Err(Tca9534Error::I2c(ErrorKind::Other))));
+    i2c.done();
+}
+
+#[test]
+fn for_straps_matches_address_constants() {
+    let expected = [
+        addresses::ADDR_000,
+        addresses::ADDR_001,
+        addresses::ADDR_010,
+        addresses::ADDR_011,
+        addresses::ADDR_100,
+        addresses::ADDR_101,
+        addresses::ADDR_110,
+        addresses::ADDR_111,
+    ];
+    for (straps, &addr) in expected.iter().enumerate() {
+        let (a2, a1, a0) = (straps & 4 != 0, straps & 2 != 0, straps & 1 != 0);
+        assert_eq!(addresses::for_straps(a2, a1, a0), addr);
+        assert_eq!(
+            addresses::for_straps_in(Family::Tca9534A, a2, a1, a0),
+            addr + 0x18
+        );
+    }
+
+    const EXPANDER_ADDR: u8 = addresses::for_straps(true, false, true);
+    assert_eq!(EXPANDER_ADDR, addresses::ADDR_101);
+}
+
+#[test]
+fn with_address_pins_uses_strap_address() {
+    let mut i2c = Mock::new(&init_transactions(0x23, 0x00));
+    let tca = Tca9534Sync::with_address_pins(i2c.clone(), false, true, true).unwrap();
+    assert_eq!(tca.address(), 0x23);
+    i2c.done();
+}
+
+#[test]
+fn new_with_family_rejects_address_outside_window() {
+    let mut i2c = Mock::new(&[]);
+    let result = Tca9534Sync::new_with_family(i2c.clone(), 0x20, Family::Tca9534A);
+    assert!(matches!(
+        result,
+        Err(Tca9534Error::Core(Tca9534CoreError::InvalidAddress))
+    ));
+    i2c.done();
+}
+
+#[test]
+fn new_with_family_accepts_high_window_address() {
+    let mut i2c = Mock::new(&init_transactions(0x3A, 0x00));
+    let tca = Tca9534Sync::new_with_family(i2c.clone(), 0x3A, Family::Pca9554A).unwrap();
+    assert_eq!(tca.address(), 0x3A);
+    i2c.done();
+}
+
+#[test]
+fn auto_detect_finds_device_in_high_window() {
+    let mut expectations: Vec<Transaction> = (0x20..=0x27)
+        .map(|addr| read_reg(addr, Register::Config, 0xFF).with_error(ErrorKind::Other))
+        .collect();
+    expectations.extend(
+        (0x38..=0x3A)
+            .map(|addr| read_reg(addr, Register::Config, 0xFF).with_error(ErrorKind::Other)),
+    );
+    expectations.push(read_reg(0x3B, Register::Config, 0xFF));
+    expectations.extend(init_transactions(0x3B, 0x00));
+
+    let mut i2c = Mock::new(&expectations);
+    let (tca, family) =
+        Tca9534Sync::auto_detect(i2c.clone(), &[Family::Tca9534, Family::Tca9534A]).unwrap();
+    assert_eq!(family, Family::Tca9534A);
+    assert_eq!(tca.address(), 0x3B);
+    i2c.done();
+}
+
+#[test]
+fn auto_detect_returns_transport_when_nothing_responds() {
+    let expectations: Vec<Transaction> = Family::Pca9554A
+        .addresses()
+        .map(|addr| read_reg(addr, Register::Config, 0xFF).with_error(ErrorKind::Other))
+        .collect();
+    let i2c = Mock::new(&expectations);
+    let Err(mut transport) = Tca9534Sync::auto_detect(i2c, &[Family::Pca9554A]) else {
+        panic!("no device should be detected");
+    };
+    transport.done();
+}
+
+#[cfg(feature = "heapless")]
+#[test]
+fn scan_lists_responding_addresses() {
+    let mut i2c = Mock::new(
+        &(0x20..=0x27)
+            .map(|addr| {
+                let probe = read_reg(addr, Register::Config, 0xFF);
+                if addr == 0x21 || addr == 0x26 {
+                    probe
+                } else {
+                    probe.with_error(ErrorKind::Other)
+                }
+            })
+            .collect::<Vec<_>>(),
+    );
+    let found: heapless::Vec<u8, 8> = Tca9534Sync::scan(&mut i2c, Family::Tca9534.addresses());
+    assert_eq!(found.as_slice(), &[0x21, 0x26]);
+    i2c.done();
+}
+
+#[test]
+fn read_pin_input_extracts_bit() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::InputPort, 0b0000_0100),
+        read_reg(ADDR, Register::InputPort, 0b0000_0100),
+    ]);
+    assert_eq!(tca.read_pin_input(2).unwrap(), PinLevel::High);
+    assert_eq!(tca.read_pin_input(3).unwrap(), PinLevel::Low);
+    i2c.done();
+}
+
+#[test]
+fn try_read_pin_input_separates_core_and_bus_errors() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::InputPort, 0b0000_0100),
+        read_reg(ADDR, Register::InputPort, 0x00).with_error(ErrorKind::Other),
+    ]);
+    assert_eq!(tca.try_read_pin_input(2).unwrap(), PinLevel::High);
+    assert!(matches!(
+        tca.try_read_pin_input(9),
+        Err(Tca9534Error::Core(Tca9534CoreError::InvalidPin))
+    ));
+    assert!(matches!(
+        tca.try_read_pin_input(2),
+        Err(Tca9534Error::I2c(Tca9534Error::I2c(ErrorKind::Other)))
+    ));
+    i2c.done();
+}
+
+#[test]
+fn set_pin_output_read_modify_write() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::OutputPort, 0b1010_0000),
+        write_reg(ADDR, Register::OutputPort, 0b1010_0001),
+        read_reg(ADDR, Register::OutputPort, 0b1010_0001),
+        write_reg(ADDR, Register::OutputPort, 0b0010_0001),
+    ]);
+    tca.set_pin_output(0, PinLevel::High).unwrap();
+    tca.set_pin_output(7, PinLevel::Low).unwrap();
+    i2c.done();
+}
+
+fn program_transactions(config: u8) -> Vec<Transaction> {
+    vec![
+        write_reg(ADDR, Register::OutputPort, 0x0F),
+        write_reg(ADDR, Register::Polarity, 0x30),
+        write_reg(ADDR, Register::Config, 0xF0),
+        read_reg(ADDR, Register::OutputPort, 0x0F),
+        read_reg(ADDR, Register::Polarity, 0x30),
+        read_reg(ADDR, Register::Config, config),
+    ]
+}
+
+#[test]
+fn program_writes_in_safe_order_and_verifies() {
+    let (mut tca, mut i2c) = driver(&program_transactions(0xF0));
+    tca.program(0xF0, 0x30, 0x0F).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn program_reports_config_mismatch() {
+    let (mut tca, mut i2c) = driver(&program_transactions(0xFF));
+    assert!(matches!(
+        tca.program(0xF0, 0x30, 0x0F),
+        Err(Tca9534Error::Core(Tca9534CoreError::VerifyFailed))
+    ));
+    i2c.done();
+}
+
+#[test]
+fn write_output_masked_keeps_unmasked_pins() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::OutputPort, 0b1010_0101),
+        write_reg(ADDR, Register::OutputPort, 0b1010_1001),
+    ]);
+    let mask = Port(0b0000_1111);
+    tca.write_output_masked(mask, Port::pin(0) | Port::pin(3))
+        .unwrap();
+    i2c.done();
+}
+
+#[test]
+fn write_seven_segment_renders_digits() {
+    // a-g on P7-P1 (reversed), decimal point on P0
+    const CATHODE: SegMap = SegMap::new([7, 6, 5, 4, 3, 2, 1], 0, false);
+    const ANODE: SegMap = SegMap::new([7, 6, 5, 4, 3, 2, 1], 0, true);
+    let (mut tca, mut i2c) = driver(&[
+        write_reg(ADDR, Register::OutputPort, 0b0110_0000),
+        write_reg(ADDR, Register::OutputPort, 0b1111_1111),
+        write_reg(ADDR, Register::OutputPort, 0b0111_0001),
+    ]);
+    tca.write_seven_segment(1, false, &CATHODE).unwrap();
+    tca.write_seven_segment(8, true, &CATHODE).unwrap();
+    tca.write_seven_segment(0xF, false, &ANODE).unwrap();
+    assert!(matches!(
+        tca.write_seven_segment(16, false, &CATHODE),
+        Err(Tca9534Error::Core(Tca9534CoreError::InvalidArgument))
+    ));
+    // Fields set directly bypass the checks of SegMap::new
+    let bad = SegMap { dp: 8, ..CATHODE };
+    assert_eq!(bad.encode(1, false), None);
+    assert!(matches!(
+        tca.write_seven_segment(1, false, &bad),
+        Err(Tca9534Error::Core(Tca9534CoreError::InvalidArgument))
+    ));
+    i2c.done();
+}
+
+#[test]
+fn mirror_drives_output_from_input() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::InputPort, 0b0000_0001),
+        read_reg(ADDR, Register::OutputPort, 0b1000_0000),
+        write_reg(ADDR, Register::OutputPort, 0b1001_0000),
+        read_reg(ADDR, Register::InputPort, 0b0000_0001),
+        read_reg(ADDR, Register::OutputPort, 0b1001_0000),
+        write_reg(ADDR, Register::OutputPort, 0b1000_0000),
+    ]);
+    assert_eq!(tca.mirror(0, 4, false).unwrap(), PinLevel::High);
+    assert_eq!(tca.mirror(0, 4, true).unwrap(), PinLevel::Low);
+    assert!(matches!(
+        tca.mirror(2, 2, false),
+        Err(Tca9534Error::Core(Tca9534CoreError::InvalidArgument))
+    ));
+    i2c.done();
+}
+
+#[test]
+fn output_pins_lists_only_outputs() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::Config, 0b1010_0110),
+        read_reg(ADDR, Register::OutputPort, 0b0100_1111),
+    ]);
+    let pins: Vec<_> = tca.output_pins().unwrap().collect();
+    assert_eq!(
+        pins,
+        [
+            (0, PinLevel::High),
+            (3, PinLevel::High),
+            (4, PinLevel::Low),
+            (6, PinLevel::High),
+        ]
+    );
+    i2c.done();
+}
+
+#[test]
+fn get_pin_output_reads_output_latch() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::OutputPort, 0b0010_0000),
+        read_reg(ADDR, Register::OutputPort, 0b0010_0000),
+    ]);
+    assert_eq!(tca.get_pin_output(5).unwrap(), PinLevel::High);
+    assert_eq!(tca.get_pin_output(4).unwrap(), PinLevel::Low);
+    i2c.done();
+}
+
+#[test]
+fn reassert_rewrites_desired_values() {
+    let (mut tca, mut i2c) = driver(&[
+        write_reg(ADDR, Register::OutputPort, 0x5A),
+        write_reg(ADDR, Register::OutputPort, 0x5A),
+        write_reg(ADDR, Register::Config, config::ALL_INPUTS),
+    ]);
+    tca.write_output_port(0x5A).unwrap();
+    tca.reassert_outputs().unwrap();
+    // Config was last written by init()
+    tca.reassert_config().unwrap();
+    i2c.done();
+}
+
+#[test]
+fn reassert_without_known_value_fails() {
+    let mut i2c = Mock::new(&[]);
+    let mut tca = Tca9534Sync::new_without_init(i2c.clone(), ADDR);
+    assert!(matches!(
+        tca.reassert_outputs(),
+        Err(Tca9534Error::Core(Tca9534CoreError::NothingToReassert))
+    ));
+    i2c.done();
+}
+
+#[test]
+fn transition_to_output_writes_output_before_config() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::OutputPort, 0b0000_0000),
+        write_reg(ADDR, Register::OutputPort, 0b0000_0100),
+        read_reg(ADDR, Register::Config, 0b1111_1111),
+        write_reg(ADDR, Register::Config, 0b1111_1011),
+    ]);
+    tca.transition_to_output(2, PinLevel::High).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn assert_reset_pulses_low_then_releases_high() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::OutputPort, 0b0010_0000),
+        write_reg(ADDR, Register::OutputPort, 0b0000_0000),
+        read_reg(ADDR, Register::Config, 0b1111_1111),
+        write_reg(ADDR, Register::Config, 0b1101_1111),
+        read_reg(ADDR, Register::OutputPort, 0b0000_0000),
+        write_reg(ADDR, Register::OutputPort, 0b0010_0000),
+    ]);
+    let mut delay = CheckedDelay::new(&[Delay::delay_us(250)]);
+    tca.assert_reset(5, PinLevel::Low, &mut delay, 250).unwrap();
+    delay.done();
+    i2c.done();
+}
+
+#[test]
+fn write_output_staggered_writes_one_pin_per_step() {
+    let cases: [(u8, u8, StaggerOrder, bool, &[u8]); 5] = [
+        (
+            0x00,
+            0b0011_1111,
+            StaggerOrder::LsbFirst,
+            false,
+            &[0x01, 0x03, 0x07, 0x0F, 0x1F, 0x3F],
+        ),
+        (
+            0x0F,
+            0xF0,
+            StaggerOrder::MsbFirst,
+            false,
+            &[0x8F, 0xCF, 0xEF, 0xFF, 0xF7, 0xF3, 0xF1, 0xF0],
+        ),
+        // Turn-offs land in the first write, then only the turn-ons are staggered
+        (
+            0x0F,
+            0xF0,
+            StaggerOrder::LsbFirst,
+            true,
+            &[0x00, 0x10, 0x30, 0x70, 0xF0],
+        ),
+        (
+            0x00,
+            0x07,
+            StaggerOrder::Custom(&[2, 0, 1]),
+            false,
+            &[0x04, 0x05, 0x07],
+        ),
+        (0x55, 0x55, StaggerOrder::LsbFirst, false, &[]),
+    ];
+    for (current, target, order, offs_first, steps) in cases {
+        let mut expectations = vec![read_reg(ADDR, Register::OutputPort, current)];
+        expectations.extend(
+            steps
+                .iter()
+                .map(|&value| write_reg(ADDR, Register::OutputPort, value)),
+        );
+        let (mut tca, mut i2c) = driver(&expectations);
+        let delays = vec![Delay::delay_us(50); steps.len().saturating_sub(1)];
+        let mut delay = CheckedDelay::new(&delays);
+        if offs_first {
+            tca.write_output_staggered_on(target, order, 50, &mut delay)
+        } else {
+            tca.write_output_staggered(target, order, 50, &mut delay)
+        }
+        .unwrap();
+        delay.done();
+        i2c.done();
+    }
+}
+
+#[test]
+fn write_output_staggered_rejects_custom_order_missing_a_pin() {
+    let (mut tca, mut i2c) = driver(&[read_reg(ADDR, Register::OutputPort, 0x00)]);
+    assert!(matches!(
+        tca.write_output_staggered(0x03, StaggerOrder::Custom(&[0]), 50, &mut NoopDelay),
+        Err(Tca9534Error::Core(Tca9534CoreError::InvalidArgument))
+    ));
+    i2c.done();
+}
+
+#[test]
+fn park_writes_safe_output_then_optionally_releases_pins() {
+    let (mut tca, mut i2c) = driver(&[
+        write_reg(ADDR, Register::OutputPort, 0x00),
+        write_reg(ADDR, Register::OutputPort, 0x81),
+        write_reg(ADDR, Register::Config, 0xFF),
+    ]);
+    tca.park(0x00, false).unwrap();
+    tca.park(0x81, true).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn toggle_pin_output_flips_bit() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::OutputPort, 0b0000_1000),
+        write_reg(ADDR, Register::OutputPort, 0b0000_0000),
+        read_reg(ADDR, Register::OutputPort, 0b0000_0000),
+        write_reg(ADDR, Register::OutputPort, 0b0100_0000),
+    ]);
+    tca.toggle_pin_output(3).unwrap();
+    tca.toggle_pin_output(6).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn set_pin_config_read_modify_write() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::Config, 0xFF),
+        write_reg(ADDR, Register::Config, 0b1111_1011),
+        read_reg(ADDR, Register::Config, 0b1111_1011),
+        write_reg(ADDR, Register::Config, 0b1111_1111),
+    ]);
+    tca.set_pin_config(2, PinConfig::Output).unwrap();
+    tca.set_pin_config(2, PinConfig::Input).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn set_pin_polarity_read_modify_write() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::Polarity, 0x00),
+        write_reg(ADDR, Register::Polarity, 0b0010_0000),
+        read_reg(ADDR, Register::Polarity, 0b0010_0000),
+        write_reg(ADDR, Register::Polarity, 0x00),
+    ]);
+    tca.set_pin_polarity(5, PinPolarity::Inverted).unwrap();
+    tca.set_pin_polarity(5, PinPolarity::Normal).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn port_operations_write_whole_register() {
+    let (mut tca, mut i2c) = driver(&[
+        write_reg(ADDR, Register::Config, 0xF0),
+        write_reg(ADDR, Register::OutputPort, 0x0F),
+        write_reg(ADDR, Register::Polarity, 0xAA),
+        read_reg(ADDR, Register::InputPort, 0x5A),
+        read_reg(ADDR, Register::OutputPort, 0x0F),
+        read_reg(ADDR, Register::Config, 0xF0),
+        read_reg(ADDR, Register::Polarity, 0xAA),
+    ]);
+    tca.set_port_config(0xF0).unwrap();
+    tca.write_output_port(0x0F).unwrap();
+    tca.set_port_polarity(0xAA).unwrap();
+    assert_eq!(tca.read_input_port().unwrap(), 0x5A);
+    assert_eq!(tca.read_output_port().unwrap(), 0x0F);
+    assert_eq!(tca.read_port_config().unwrap(), 0xF0);
+    assert_eq!(tca.read_port_polarity().unwrap(), 0xAA);
+    i2c.done();
+}
+
+const BOARD_PROFILE: InitialConfig = InitialConfig::new()
+    .with_outputs(&[0, 1], PinLevel::High)
+    .with_outputs(&[2, 3], PinLevel::Low)
+    .with_inverted(&[7]);
+
+#[test]
+fn check_against_accepts_matching_device() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::InputPort, 0x00),
+        read_reg(ADDR, Register::OutputPort, 0x03),
+        read_reg(ADDR, Register::Polarity, 0x80),
+        read_reg(ADDR, Register::Config, 0xF0),
+    ]);
+    let diff = tca.check_against(&BOARD_PROFILE).unwrap();
+    assert!(diff.is_conformant());
+    assert_eq!(diff, ConfigDiff::default());
+    i2c.done();
+}
+
+#[test]
+fn check_against_reports_differing_bits_per_register() {
+    // Wrong variant: pin 3 is an input, pin 7 not inverted, pin 1 latched low
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::InputPort, 0x00),
+        read_reg(ADDR, Register::OutputPort, 0x01),
+        read_reg(ADDR, Register::Polarity, 0x00),
+        read_reg(ADDR, Register::Config, 0xF8),
+    ]);
+    let diff = tca.check_against(&BOARD_PROFILE).unwrap();
+    assert_eq!(
+        diff,
+        ConfigDiff {
+            output: 0b0000_0010,
+            polarity: 0b1000_0000,
+            config: 0b0000_1000,
+        }
+    );
+    assert!(!diff.is_conformant());
+    assert_eq!(diff.pins(), 0b1000_1010);
+    i2c.done();
+}
+
+#[test]
+fn config_diff_compares_snapshots_offline() {
+    let snapshot = RegisterSnapshot {
+        input: 0xFF,
+        output: 0x03,
+        polarity: 0x80,
+        config: 0x00,
+    };
+    let diff = ConfigDiff::new(&BOARD_PROFILE, &snapshot);
+    assert_eq!(diff.pins(), 0xF0);
+    assert_eq!(diff.config, 0xF0);
+}
+
+#[test]
+fn detect_output_faults_flags_driven_high_output_reading_low() {
+    // Pins 0-3 outputs driven high; pin 2 reads low, pin 4 is an inverted input
+    let (mut tca, mut i2c) = driver(&snapshot_reads(0x1B, 0x0F, 0x10, 0xF0));
+    assert_eq!(tca.detect_output_faults().unwrap(), 0b0000_0100);
+    i2c.done();
+}
+
+#[test]
+fn set_pins_drives_masked_pins_low() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::OutputPort, 0xFF),
+        write_reg(ADDR, Register::OutputPort, 0xF6),
+    ]);
+    tca.set_pins(Port::pin(0) | Port::pin(3), PinLevel::Low)
+        .unwrap();
+    i2c.done();
+}
+
+#[test]
+fn degraded_policy_serves_cached_input_until_bus_recovers() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::InputPort, 0x5A).with_error(ErrorKind::Other),
+        read_reg(ADDR, Register::InputPort, 0x5A),
+        read_reg(ADDR, Register::InputPort, 0x00).with_error(ErrorKind::Other),
+        read_reg(ADDR, Register::InputPort, 0x00).with_error(ErrorKind::Other),
+        read_reg(ADDR, Register::InputPort, 0x00).with_error(ErrorKind::Other),
+        write_reg(ADDR, Register::OutputPort, 0x01).with_error(ErrorKind::Other),
+        read_reg(ADDR, Register::InputPort, 0x11),
+        read_reg(ADDR, Register::InputPort, 0x00).with_error(ErrorKind::Other),
+    ]);
+    tca.set_degraded_policy(DegradedPolicy::ServeCached { max_age: 2 });
+
+    // Nothing cached yet
+    assert!(tca.read_input_port().is_err());
+    assert_eq!(tca.read_input_port().unwrap(), 0x5A);
+    assert!(!tca.last_read_was_stale());
+
+    // The bus drops: two stale reads, then the error
+    assert_eq!(tca.read_pin_input(1).unwrap(), PinLevel::High);
+    assert!(tca.last_read_was_stale());
+    assert_eq!(tca.read_input_port().unwrap(), 0x5A);
+    assert!(tca.read_input_port().is_err());
+    assert!(tca.last_read_was_stale());
+
+    // Writes still fail
+    assert!(tca.write_output_port(0x01).is_err());
+
+    // The bus returns
+    assert_eq!(tca.read_input_port().unwrap(), 0x11);
+    assert!(!tca.last_read_was_stale());
+    assert_eq!(tca.read_input_port().unwrap(), 0x11);
+    assert!(tca.last_read_was_stale());
+    i2c.done();
+}
+
+#[test]
+fn compare_and_write_output_writes_on_match() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::OutputPort, 0x0F),
+        write_reg(ADDR, Register::OutputPort, 0x1F),
+    ]);
+    assert_eq!(tca.compare_and_write_output(0x0F, 0x1F).unwrap(), Ok(()));
+    assert_eq!(tca.cached_output(), Some(0x1F));
+    i2c.done();
+}
+
+#[test]
+fn compare_and_write_output_returns_observed_value_on_mismatch() {
+    let (mut tca, mut i2c) = driver(&[read_reg(ADDR, Register::OutputPort, 0x8F)]);
+    assert_eq!(tca.compare_and_write_output(0x0F, 0x1F).unwrap(), Err(0x8F));
+    i2c.done();
+}
+
+#[test]
+fn reserved_pin_is_rejected_without_bus_traffic() {
+    let mut i2c = Mock::new(&after_init(
+        ADDR,
+        &[
+            read_reg(ADDR, Register::OutputPort, 0x00),
+            write_reg(ADDR, Register::OutputPort, 0x08),
+        ],
+    ));
+    let mut tca = Tca9534Sync::new_with_usable_pins(i2c.clone(), ADDR, 0x0F).unwrap();
+    assert_eq!(tca.usable_pins(), 0x0F);
+    assert!(matches!(
+        tca.set_pin_output(5, PinLevel::High),
+        Err(Tca9534Error::Core(Tca9534CoreError::PinReserved))
+    ));
+    assert!(matches!(
+        tca.read_pin_input(5),
+        Err(Tca9534Error::Core(Tca9534CoreError::PinReserved))
+    ));
+    assert!(matches!(
+        tca.set_pins(Port::pin(3) | Port::pin(5), PinLevel::High),
+        Err(Tca9534Error::Core(Tca9534CoreError::PinReserved))
+    ));
+    assert!(matches!(
+        tca.write_output_masked(Port(0xF0), Port(0xFF)),
+        Err(Tca9534Error::Core(Tca9534CoreError::PinReserved))
+    ));
+    assert!(matches!(
+        tca.loopback_test(&[(0, 5)], &mut NoopDelay, 10),
+        Err(Tca9534Error::Core(Tca9534CoreError::PinReserved))
+    ));
+    // Wired pins work as usual
+    tca.set_pin_output(3, PinLevel::High).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn invalid_pin_is_rejected_without_bus_traffic() {
+    let (mut tca, mut i2c) = driver(&[]);
+    assert!(matches!(
+        tca.read_pin_input(8),
+        Err(Tca9534Error::Core(Tca9534CoreError::InvalidPin))
+    ));
+    assert!(matches!(
+        tca.set_pin_output(8, PinLevel::High),
+        Err(Tca9534Error::Core(Tca9534CoreError::InvalidPin))
+    ));
+    assert!(matches!(
+        tca.toggle_pin_output(9),
+        Err(Tca9534Error::Core(Tca9534CoreError::InvalidPin))
+    ));
+    assert!(matches!(
+        tca.set_pin_config(255, PinConfig::Output),
+        Err(Tca9534Error::Core(Tca9534CoreError::InvalidPin))
+    ));
+    assert!(matches!(
+        tca.set_pin_polarity(8, PinPolarity::Inverted),
+        Err(Tca9534Error::Core(Tca9534CoreError::InvalidPin))
+    ));
+    i2c.done();
+}
+
+#[test]
+fn read_error_aborts_read_modify_write() {
+    let (mut tca, mut i2c) =
+        driver(&[read_reg(ADDR, Register::OutputPort, 0x00).with_error(ErrorKind::Other)]);
+    assert!(matches!(
+        tca.set_pin_output(1, PinLevel::High),
+        Err(Tca9534Error::I2c(ErrorKind::Other))
+    ));
+    i2c.done();
+}
+
+#[test]
+fn read_pin_active_combines_polarity_and_active_level() {
+    // Pin 2 is electrically low in every case; the Input Port shows it XOR polarity
+    let cases = [
+        (0x00, PinLevel::High, false),
+        (0x04, PinLevel::High, false),
+        (0x00, PinLevel::Low, true),
+        (0x04, PinLevel::Low, true),
+    ];
+    for (polarity, active_level, expected) in cases {
+        let (mut tca, mut i2c) = driver(&[
+            read_reg(ADDR, Register::InputPort, polarity),
+            read_reg(ADDR, Register::Polarity, polarity),
+        ]);
+        tca.declare_pin_active_level(2, active_level).unwrap();
+        assert_eq!(tca.pin_active_level(2).unwrap(), active_level);
+        assert_eq!(tca.read_pin_active(2).unwrap(), expected);
+        assert!(matches!(
+            tca.pin_active_level(8),
+            Err(Tca9534Error::Core(Tca9534CoreError::InvalidPin))
+        ));
+        i2c.done();
+    }
+}
+
+#[test]
+fn logical_level_converts_to_and_from_electrical() {
+    assert_eq!(
+        LogicalLevel::from_level(PinLevel::Low, PinLevel::Low),
+        LogicalLevel::Active
+    );
+    assert_eq!(
+        LogicalLevel::from_level(PinLevel::Low, PinLevel::High),
+        LogicalLevel::Inactive
+    );
+    assert_eq!(
+        LogicalLevel::Inactive.to_level(PinLevel::Low),
+        PinLevel::High
+    );
+    assert_eq!(LogicalLevel::from(true), LogicalLevel::Active);
+    assert!(!bool::from(LogicalLevel::Inactive));
+}
+
+#[test]
+fn inputs_changed_since_init_compares_with_captured_value() {
+    let mut expectations = init_transactions(ADDR, 0b0001_0000);
+    expectations.push(read_reg(ADDR, Register::InputPort, 0b0001_0000));
+    expectations.push(read_reg(ADDR, Register::InputPort, 0b0001_0001));
+    let mut i2c = Mock::new(&expectations);
+    let mut tca = Tca9534Sync::new(i2c.clone(), ADDR).unwrap();
+    assert!(!tca.inputs_changed_since_init().unwrap());
+    assert!(tca.inputs_changed_since_init().unwrap());
+    i2c.done();
+}
+
+#[test]
+fn read_input_port_checked_flags_all_ones() {
+    let mut i2c = Mock::new(&after_init(
+        ADDR,
+        &[
+            read_reg(ADDR, Register::InputPort, 0xFF),
+            read_reg(ADDR, Register::InputPort, 0x5A),
+        ],
+    ));
+    let mut tca = Tca9534Sync::new(i2c.clone(), ADDR).unwrap();
+    let reading = tca.read_input_port_checked().unwrap();
+    assert_eq!(reading.value, 0xFF);
+    assert!(reading.suspicious);
+    assert!(!tca.read_input_port_checked().unwrap().suspicious);
+    i2c.done();
+}
+
+#[test]
+fn power_on_defaults_match_snapshot_constant() {
+    for reg in [Register::OutputPort, Register::Polarity, Register::Config] {
+        assert_eq!(
+            reg.power_on_default(),
+            Some(defaults::POWER_ON_SNAPSHOT.get(reg))
+        );
+    }
+    assert_eq!(Register::InputPort.power_on_default(), None);
+    assert_eq!(
+        Register::OutputPort.power_on_default(),
+        Some(config::POR_OUTPUT_DEFAULT)
+    );
+}
+
+#[test]
+fn detect_device_reset_checks_writable_registers() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::InputPort, 0x12),
+        read_reg(ADDR, Register::OutputPort, 0x00),
+        read_reg(ADDR, Register::Polarity, 0x00),
+        read_reg(ADDR, Register::Config, 0xFF),
+        read_reg(ADDR, Register::InputPort, 0x34),
+        read_reg(ADDR, Register::OutputPort, 0xFF),
+        read_reg(ADDR, Register::Polarity, 0x00),
+        read_reg(ADDR, Register::Config, 0xFF),
+    ]);
+    assert!(!tca.detect_device_reset().unwrap());
+    assert!(tca.detect_device_reset().unwrap());
+    i2c.done();
+}
+
+#[test]
+fn batch_flush_writes_each_register_once() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::OutputPort, 0x00),
+        read_reg(ADDR, Register::Config, 0xFF),
+        write_reg(ADDR, Register::OutputPort, 0b0000_0001),
+        write_reg(ADDR, Register::Config, 0b1111_1100),
+    ]);
+    let mut batch = tca.begin_batch();
+    batch.set_pin_output(0, PinLevel::High).unwrap();
+    batch.set_pin_config(0, PinConfig::Output).unwrap();
+    batch.set_pin_config(1, PinConfig::Output).unwrap();
+    batch.flush().unwrap();
+    drop(batch);
+    i2c.done();
+}
+
+#[test]
+fn batch_is_dirty_until_flushed() {
+    let (mut tca, mut i2c) = driver(&[write_reg(ADDR, Register::Config, 0x0F)]);
+    let mut batch = tca.begin_batch();
+    assert!(!batch.is_dirty());
+    batch.set_port_config(0x0F);
+    assert!(batch.is_dirty());
+    batch.flush().unwrap();
+    assert!(!batch.is_dirty());
+    drop(batch);
+    i2c.done();
+}
+
+#[test]
+fn batch_debug_output_shows_only_shadows() {
+    let (mut tca, mut i2c) = driver(&[write_reg(ADDR, Register::Config, 0x0F)]);
+    let mut batch = tca.begin_batch();
+    batch.set_port_config(0x0F);
+    let text = format!("{:?}", batch);
+    assert!(text.starts_with("Batch { output: "), "{}", text);
+    assert!(
+        text.contains("config: Shadow { value: Some(15), dirty: true }"),
+        "{}",
+        text
+    );
+    assert!(!text.contains("driver"), "{}", text);
+    assert!(text.ends_with(", .. }"), "{}", text);
+    drop(batch);
+    i2c.done();
+}
+
+#[test]
+fn batch_rejects_reserved_pin() {
+    let mut i2c = Mock::new(&init_transactions(ADDR, 0x00));
+    let mut tca = Tca9534Sync::new_with_usable_pins(i2c.clone(), ADDR, 0x0F).unwrap();
+    {
+        let mut batch = tca.begin_batch();
+        assert!(matches!(
+            batch.set_pin_output(5, PinLevel::High),
+            Err(Tca9534Error::Core(Tca9534CoreError::PinReserved))
+        ));
+        assert!(matches!(
+            batch.toggle_pin_output(5),
+            Err(Tca9534Error::Core(Tca9534CoreError::PinReserved))
+        ));
+        assert!(!batch.is_dirty());
+    }
+    assert!(matches!(
+        tca.declare_pin_active_level(5, PinLevel::Low),
+        Err(Tca9534Error::Core(Tca9534CoreError::PinReserved))
+    ));
+    i2c.done();
+}
+
+#[test]
+fn batch_flushes_on_drop() {
+    let (mut tca, mut i2c) = driver(&[
+        write_reg(ADDR, Register::OutputPort, 0x55),
+        write_reg(ADDR, Register::Polarity, 0x0F),
+    ]);
+    {
+        let mut batch = tca.begin_batch();
+        batch.set_port_polarity(0x0F);
+        batch.write_output_port(0x55);
+    }
+    i2c.done();
+}
+
+#[test]
+fn reader_forwards_reads_to_the_device() {
+    let mut reads = vec![
+        read_reg(ADDR, Register::InputPort, 0x81),
+        read_reg(ADDR, Register::InputPort, 0x04),
+        read_reg(ADDR, Register::Config, 0xF0),
+    ];
+    reads.extend(snapshot_reads(0x1E, 0x0F, 0x30, 0xF0));
+    let (mut tca, mut i2c) = driver(&reads);
+    let mut reader = tca.reader();
+    assert_eq!(reader.address(), ADDR);
+    assert_eq!(reader.read_input_port().unwrap(), 0x81);
+    assert_eq!(reader.read_pin_input(2).unwrap(), PinLevel::High);
+    assert_eq!(reader.read_port_config().unwrap(), 0xF0);
+    assert_eq!(reader.state_dump().unwrap().registers.input, 0x1E);
+    i2c.done();
+}
+
+#[test]
+fn cached_state_follows_driver_writes_and_resync() {
+    let mut i2c = Mock::new(&after_init(
+        ADDR,
+        &[
+            Transaction::write(ADDR, vec![Register::OutputPort.addr(), 0x0F]),
+            read_reg(ADDR, Register::InputPort, 0x00),
+            read_reg(ADDR, Register::OutputPort, 0x0F),
+            read_reg(ADDR, Register::Polarity, 0x00),
+            read_reg(ADDR, Register::Config, 0xF0),
+            write_reg(ADDR, Register::Polarity, 0x01).with_error(ErrorKind::Other),
+            write_reg(ADDR, Register::OutputPort, 0xAA),
+        ],
+    ));
+    let mut tca = Tca9534Sync::new_without_init(i2c.clone(), ADDR);
+    assert_eq!(tca.cached_output(), None);
+    tca.init().unwrap();
+    assert_eq!(tca.cached_output(), Some(0x00));
+    assert_eq!(tca.cached_config(), Some(0xFF));
+    assert_eq!(tca.cached_polarity(), Some(0x00));
+
+    // A write behind the driver's back leaves the cache stale until resync
+    embedded_hal::i2c::I2c::write(tca.transport_mut(), ADDR, &[0x01, 0x0F]).unwrap();
+    assert_eq!(tca.cached_output(), Some(0x00));
+    tca.resync().unwrap();
+    assert_eq!(tca.cached_output(), Some(0x0F));
+    assert_eq!(tca.cached_config(), Some(0xF0));
+
+    // A failed write may or may not have landed
+    assert!(tca.set_port_polarity(0x01).is_err());
+    assert_eq!(tca.cached_polarity(), None);
+
+    tca.assume_output(0xAA);
+    assert_eq!(tca.cached_output(), Some(0xAA));
+    tca.reassert_outputs().unwrap();
+    i2c.done();
+}
+
+#[test]
+fn force_write_then_stale_caches_recover_through_resync() {
+    let mut expectations = vec![
+        write_reg(ADDR, Register::OutputPort, 0xFF),
+        write_reg(ADDR, Register::OutputPort, 0x00),
+    ];
+    expectations.extend(snapshot_reads(0x00, 0x00, 0x00, 0xFF));
+    let (mut tca, mut i2c) = driver(&expectations);
+    tca.write_output_port(0xFF).unwrap();
+
+    tca.force_write_output_port(0x00).unwrap();
+    // The bookkeeping still holds the pre-emergency value until marked stale
+    assert_eq!(tca.cached_output(), Some(0xFF));
+    tca.mark_caches_stale();
+    assert_eq!(tca.cached_output(), None);
+    assert!(matches!(
+        tca.reassert_outputs(),
+        Err(Tca9534Error::Core(Tca9534CoreError::NothingToReassert))
+    ));
+
+    tca.resync().unwrap();
+    assert_eq!(tca.cached_output(), Some(0x00));
+    assert!(tca.apply_config(&InitialConfig::new()).unwrap().is_empty());
+    i2c.done();
+}
+
+#[test]
+fn batch_flush_output_skips_unchanged_value() {
+    let (mut tca, mut i2c) = driver(&[write_reg(ADDR, Register::OutputPort, 0b0000_0001)]);
+    let mut batch = tca.begin_batch();
+    // init() wrote 0x00, so an unchanged shadow issues no write
+    batch.write_output_port(0x00);
+    batch.flush_output().unwrap();
+
+    batch.set_pin_output(0, PinLevel::High).unwrap();
+    batch.flush_output().unwrap();
+    batch.flush_output().unwrap();
+    drop(batch);
+    i2c.done();
+}
+
+#[test]
+fn strict_mode_rejects_pin_operations_before_init() {
+    let mut i2c = Mock::new(&after_init(
+        ADDR,
+        &[
+            read_reg(ADDR, Register::OutputPort, 0x00),
+            write_reg(ADDR, Register::OutputPort, 0x04),
+        ],
+    ));
+    let mut tca = Tca9534Sync::new_without_init(i2c.clone(), ADDR);
+    tca.set_strict_init(true);
+    assert!(matches!(
+        tca.set_pin_output(2, PinLevel::High),
+        Err(Tca9534Error::Core(Tca9534CoreError::NotInitialized))
+    ));
+
+    tca.ensure_initialized().unwrap();
+    tca.ensure_initialized().unwrap();
+    assert!(tca.is_initialized());
+    tca.set_pin_output(2, PinLevel::High).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn read_named_reports_mapped_pins_only() {
+    const MAP: PinMap = PinMap::new().with(1, "DOOR").with(6, "LIMIT");
+    let (mut tca, mut i2c) = driver(&[read_reg(ADDR, Register::InputPort, 0b0100_0001)]);
+    let levels = tca.read_named(&MAP).unwrap();
+    assert_eq!(levels.get("DOOR"), Some(PinLevel::Low));
+    assert_eq!(levels.get("LIMIT"), Some(PinLevel::High));
+    assert_eq!(levels.get("OTHER"), None);
+    assert_eq!(
+        levels.iter().collect::<Vec<_>>(),
+        [("DOOR", PinLevel::Low), ("LIMIT", PinLevel::High)]
+    );
+    i2c.done();
+}
+
+#[test]
+fn loopback_test_reports_broken_pair_and_restores_state() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::Config, 0xFF),
+        read_reg(ADDR, Register::OutputPort, 0x00),
+        read_reg(ADDR, Register::Polarity, 0b0001_0000),
+        // P0 -> P4, with P4 inverted by the Polarity register
+        write_reg(ADDR, Register::OutputPort, 0x00),
+        write_reg(ADDR, Register::Config, 0b1111_1110),
+        read_reg(ADDR, Register::InputPort, 0b0001_0000),
+        write_reg(ADDR, Register::OutputPort, 0b0000_0001),
+        write_reg(ADDR, Register::Config, 0b1111_1110),
+        read_reg(ADDR, Register::InputPort, 0b0000_0001),
+        // P1 -> P5, not connected
+        write_reg(ADDR, Register::OutputPort, 0x00),
+        write_reg(ADDR, Register::Config, 0b1111_1101),
+        read_reg(ADDR, Register::InputPort, 0b0001_0000),
+        write_reg(ADDR, Register::OutputPort, 0b0000_0010),
+        write_reg(ADDR, Register::Config, 0b1111_1101),
+        read_reg(ADDR, Register::InputPort, 0b0001_0010),
+        // Restore
+        write_reg(ADDR, Register::OutputPort, 0x00),
+        write_reg(ADDR, Register::Config, 0xFF),
+    ]);
+    let report = tca
+        .loopback_test(&[(0, 4), (1, 5)], &mut NoopDelay, 10)
+        .unwrap();
+    let results: Vec<_> = report.iter().copied().collect();
+    assert_eq!(results.len(), 2);
+    assert!(results[0].passed());
+    assert!(!results[1].passed());
+    assert_eq!(results[1].observed_high, PinLevel::Low);
+    assert!(!report.all_passed());
+    assert_eq!(report.failures().count(), 1);
+    i2c.done();
+}
+
+#[test]
+fn wait_high_polls_until_pin_is_high() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::InputPort, 0x00),
+        read_reg(ADDR, Register::InputPort, 0b1111_1011),
+        read_reg(ADDR, Register::InputPort, 0b0000_0100),
+    ]);
+    let mut delay = CheckedDelay::new(&[Delay::delay_us(100), Delay::delay_us(100)]);
+    tca.wait_high(2, &mut delay, 100, 1_000).unwrap();
+    delay.done();
+    i2c.done();
+}
+
+#[test]
+fn wait_low_times_out_after_final_read() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::InputPort, 0xFF),
+        read_reg(ADDR, Register::InputPort, 0xFF),
+        read_reg(ADDR, Register::InputPort, 0xFF),
+        read_reg(ADDR, Register::InputPort, 0xFF),
+    ]);
+    // The last delay is shortened to end exactly at the timeout
+    let mut delay = CheckedDelay::new(&[
+        Delay::delay_us(400),
+        Delay::delay_us(400),
+        Delay::delay_us(200),
+    ]);
+    assert!(matches!(
+        tca.wait_low(5, &mut delay, 400, 1_000),
+        Err(Tca9534Error::Core(Tca9534CoreError::Timeout))
+    ));
+    delay.done();
+    i2c.done();
+}
+
+#[test]
+fn read_pin_sampled_returns_majority_level() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::InputPort, 0b0000_0010),
+        read_reg(ADDR, Register::InputPort, 0b0000_0000),
+        read_reg(ADDR, Register::InputPort, 0b0000_0010),
+    ]);
+    let mut delay = CheckedDelay::new(&[Delay::delay_us(50), Delay::delay_us(50)]);
+    assert_eq!(
+        tca.read_pin_sampled(1, 3, &mut delay, 50).unwrap(),
+        PinLevel::High
+    );
+    delay.done();
+    i2c.done();
+}
+
+#[test]
+fn read_pin_sampled_rejects_even_sample_count() {
+    let (mut tca, mut i2c) = driver(&[]);
+    for samples in [0, 2] {
+        assert!(matches!(
+            tca.read_pin_sampled(1, samples, &mut NoopDelay, 50),
+            Err(Tca9534Error::Core(Tca9534CoreError::InvalidArgument))
+        ));
+    }
+    i2c.done();
+}
+
+#[test]
+fn loopback_test_rejects_same_pin_pair() {
+    let (mut tca, mut i2c) = driver(&[]);
+    assert!(matches!(
+        tca.loopback_test(&[(3, 3)], &mut NoopDelay, 10),
+        Err(Tca9534Error::Core(Tca9534CoreError::InvalidArgument))
+    ));
+    i2c.done();
+}
+
+#[test]
+fn poll_register_fast_sends_command_byte_once() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::InputPort, 0x00),
+        Transaction::read(ADDR, vec![0x00]),
+        Transaction::read(ADDR, vec![0x04]),
+    ]);
+    let value = tca
+        .poll_register_fast(Register::InputPort, 3, |value| value & 0x04 != 0)
+        .unwrap();
+    assert_eq!(value, 0x04);
+    i2c.done();
+}
+
+#[test]
+fn poll_register_fast_times_out_after_max_polls() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::InputPort, 0x00),
+        Transaction::read(ADDR, vec![0x00]),
+    ]);
+    assert!(matches!(
+        tca.poll_register_fast(Register::InputPort, 2, |value| value != 0),
+        Err(Tca9534Error::Core(Tca9534CoreError::Timeout))
+    ));
+    assert!(matches!(
+        tca.poll_register_fast(Register::InputPort, 0, |_| true),
+        Err(Tca9534Error::Core(Tca9534CoreError::InvalidArgument))
+    ));
+    i2c.done();
+}
+
+#[test]
+fn walking_pattern_walks_ones_then_zeros() {
+    assert_eq!(&WALKING_PATTERN[..3], &[0x01, 0x02, 0x04]);
+    assert_eq!(WALKING_PATTERN[7], 0x80);
+    assert_eq!(&WALKING_PATTERN[8..10], &[0xFE, 0xFD]);
+    assert_eq!(WALKING_PATTERN[15], 0x7F);
+}
+
+#[test]
+fn self_test_pattern_drives_all_states_without_verify() {
+    let mut expectations = vec![
+        read_reg(ADDR, Register::Config, 0xF0),
+        read_reg(ADDR, Register::OutputPort, 0x05),
+        read_reg(ADDR, Register::Polarity, 0x00),
+    ];
+    for (index, &state) in WALKING_PATTERN.iter().enumerate() {
+        expectations.push(write_reg(ADDR, Register::OutputPort, state));
+        if index == 0 {
+            expectations.push(write_reg(ADDR, Register::Config, 0x00));
+        }
+    }
+    expectations.push(write_reg(ADDR, Register::OutputPort, 0x05));
+    expectations.push(write_reg(ADDR, Register::Config, 0xF0));
+
+    let (mut tca, mut i2c) = driver(&expectations);
+    assert_eq!(
+        tca.self_test_pattern(&mut NoopDelay, 1, false).unwrap(),
+        None
+    );
+    i2c.done();
+}
+
+#[test]
+fn walking_ones_writes_each_bit_then_restores() {
+    let mut expectations = vec![read_reg(ADDR, Register::OutputPort, 0x5A)];
+    for bit in 0..8 {
+        expectations.push(write_reg(ADDR, Register::OutputPort, 1 << bit));
+    }
+    expectations.push(write_reg(ADDR, Register::OutputPort, 0x5A));
+
+    let (mut tca, mut i2c) = driver(&expectations);
+    let mut delay = CheckedDelay::new(&vec![Delay::delay_us(200); 8]);
+    assert_eq!(tca.walking_ones(&mut delay, 200, false).unwrap(), None);
+    delay.done();
+    i2c.done();
+}
+
+#[test]
+fn walking_ones_skips_reserved_pins() {
+    let mut expectations = vec![read_reg(ADDR, Register::OutputPort, 0x50)];
+    for bit in 0..4 {
+        expectations.push(write_reg(ADDR, Register::OutputPort, 0x50 | 1 << bit));
+    }
+    expectations.push(write_reg(ADDR, Register::OutputPort, 0x50));
+
+    let mut i2c = Mock::new(&after_init(ADDR, &expectations));
+    let mut tca = Tca9534Sync::new_with_usable_pins(i2c.clone(), ADDR, 0x0F).unwrap();
+    assert_eq!(tca.walking_ones(&mut NoopDelay, 1, false).unwrap(), None);
+    i2c.done();
+}
+
+#[test]
+fn self_test_pattern_leaves_reserved_pins_alone() {
+    // P4-P7 are not wired: they stay inputs, keep their output bits and read garbage
+    let mut expectations = vec![
+        read_reg(ADDR, Register::Config, 0xF3),
+        read_reg(ADDR, Register::OutputPort, 0x50),
+        read_reg(ADDR, Register::Polarity, 0x00),
+    ];
+    for (index, &state) in WALKING_PATTERN.iter().enumerate() {
+        if index % 8 >= 4 {
+            continue;
+        }
+        let driven = (state & 0x0F) | 0x50;
+        expectations.push(write_reg(ADDR, Register::OutputPort, driven));
+        if index == 0 {
+            expectations.push(write_reg(ADDR, Register::Config, 0xF0));
+        }
+        expectations.push(read_reg(ADDR, Register::InputPort, (driven & 0x0F) | 0xA0));
+    }
+    expectations.push(write_reg(ADDR, Register::OutputPort, 0x50));
+    expectations.push(write_reg(ADDR, Register::Config, 0xF3));
+
+    let mut i2c = Mock::new(&after_init(ADDR, &expectations));
+    let mut tca = Tca9534Sync::new_with_usable_pins(i2c.clone(), ADDR, 0x0F).unwrap();
+    assert_eq!(
+        tca.self_test_pattern(&mut NoopDelay, 1, true).unwrap(),
+        None
+    );
+    i2c.done();
+}
+
+#[test]
+fn walking_zeros_verify_reports_stuck_output() {
+    // Output Port bit 1 reads back stuck high
+    let mut expectations = vec![read_reg(ADDR, Register::OutputPort, 0x00)];
+    for state in [0xFE, 0xFD] {
+        expectations.push(write_reg(ADDR, Register::OutputPort, state));
+        expectations.push(read_reg(ADDR, Register::OutputPort, state | 0x02));
+    }
+    expectations.push(write_reg(ADDR, Register::OutputPort, 0x00));
+
+    let (mut tca, mut i2c) = driver(&expectations);
+    let mismatch = tca.walking_zeros(&mut NoopDelay, 1, true).unwrap().unwrap();
+    assert_eq!(mismatch.expected, 0xFD);
+    assert_eq!(mismatch.faulty_pins(), 0x02);
+    i2c.done();
+}
+
+#[test]
+fn self_test_pattern_finds_bridged_pins() {
+    // P2 and P3 shorted together: the bridge pulls both low when they differ
+    let bridged = |value: u8| {
+        if (value >> 2) & 1 != (value >> 3) & 1 {
+            value & !0b0000_1100
+        } else {
+            value
+        }
+    };
+    let mut expectations = vec![
+        read_reg(ADDR, Register::Config, 0xFF),
+        read_reg(ADDR, Register::OutputPort, 0x00),
+        read_reg(ADDR, Register::Polarity, 0x00),
+    ];
+    for (index, &state) in WALKING_PATTERN[..3].iter().enumerate() {
+        expectations.push(write_reg(ADDR, Register::OutputPort, state));
+        if index == 0 {
+            expectations.push(write_reg(ADDR, Register::Config, 0x00));
+        }
+        expectations.push(read_reg(ADDR, Register::InputPort, bridged(state)));
+    }
+    expectations.push(write_reg(ADDR, Register::OutputPort, 0x00));
+    expectations.push(write_reg(ADDR, Register::Config, 0xFF));
+
+    let (mut tca, mut i2c) = driver(&expectations);
+    let mismatch = tca
+        .self_test_pattern(&mut NoopDelay, 1, true)
+        .unwrap()
+        .unwrap();
+    assert_eq!(mismatch.expected, 0x04);
+    assert_eq!(mismatch.observed, 0x00);
+    assert_eq!(mismatch.faulty_pins(), 0x04);
+    i2c.done();
+}
+
+fn snapshot_reads(input: u8, output: u8, polarity: u8, config: u8) -> Vec<Transaction> {
+    vec![
+        read_reg(ADDR, Register::InputPort, input),
+        read_reg(ADDR, Register::OutputPort, output),
+        read_reg(ADDR, Register::Polarity, polarity),
+        read_reg(ADDR, Register::Config, config),
+    ]
+}
+
+#[test]
+fn dump_state_renders_per_pin_report() {
+    let (mut tca, mut i2c) = driver(&snapshot_reads(0x1E, 0x0F, 0x30, 0xF0));
+    let mut out = String::new();
+    tca.dump_state(&mut out).unwrap();
+    assert_eq!(
+        out,
+        "TCA9534 @ 0x20: input=0x1e output=0x0f polarity=0x30 config=0xf0\n\
+         P0: out out=H in=L MISMATCH\n\
+         P1: out out=H in=H\n\
+         P2: out out=H in=H\n\
+         P3: out out=H in=H\n\
+         P4: in  out=L in=H inverted\n\
+         P5: in  out=L in=L inverted\n\
+         P6: in  out=L in=L\n\
+         P7: in  out=L in=L\n"
+    );
+    i2c.done();
+}
+
+#[test]
+fn describe_reports_registers_cache_and_label() {
+    let (tca, mut i2c) = driver(&snapshot_reads(0x1E, 0x0F, 0x30, 0xF0));
+    let mut tca = tca.with_label("status-leds");
+    let description = tca.describe().unwrap();
+    assert_eq!(
+        description,
+        DeviceDescription {
+            state: StateDump {
+                address: ADDR,
+                registers: RegisterSnapshot {
+                    input: 0x1E,
+                    output: 0x0F,
+                    polarity: 0x30,
+                    config: 0xF0,
+                },
+            },
+            family: Some(Family::Tca9534),
+            label: Some("status-leds"),
+            initialized: true,
+            cached_output: Some(0x00),
+            cached_polarity: Some(0x00),
+            cached_config: Some(0xFF),
+        }
+    );
+    assert_eq!(description.cache_matches(Register::OutputPort), Some(false));
+    assert_eq!(description.cache_matches(Register::InputPort), None);
+    assert_eq!(
+        description.pin(0),
+        Ok(PinDescription {
+            config: PinConfig::Output,
+            polarity: PinPolarity::Normal,
+            output: PinLevel::High,
+            input: PinLevel::Low,
+            mismatch: true,
+        })
+    );
+    let pin4 = description.pin(4).unwrap();
+    assert_eq!(pin4.polarity, PinPolarity::Inverted);
+    assert_eq!(pin4.config, PinConfig::Input);
+    assert_eq!(description.pin(8), Err(Tca9534CoreError::InvalidPin));
+    i2c.done();
+}
+
+struct FailingWriter;
+
+impl core::fmt::Write for FailingWriter {
+    fn write_str(&mut self, _: &str) -> core::fmt::Result {
+        Err(core::fmt::Error)
+    }
+}
+
+#[test]
+fn dump_state_reports_writer_failure() {
+    let (mut tca, mut i2c) = driver(&snapshot_reads(0x00, 0x00, 0x00, 0xFF));
+    assert!(matches!(
+        tca.dump_state(&mut FailingWriter),
+        Err(DumpError::Format)
+    ));
+    i2c.done();
+}
+
+fn digital_error_kind<E: embedded_hal::digital::Error>(
+    error: &E,
+) -> embedded_hal::digital::ErrorKind {
+    error.kind()
+}
+
+#[test]
+fn modify_register_applies_closure_to_config() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::Config, 0xFF),
+        write_reg(ADDR, Register::Config, 0xF0),
+    ]);
+    let value = tca
+        .modify_register(Register::Config, |config| config & 0xF0)
+        .unwrap();
+    assert_eq!(value, 0xF0);
+    i2c.done();
+}
+
+#[test]
+fn pin_setters_issue_one_read_and_one_write() {
+    let (mut tca, mut i2c) = driver(&[
+        read_reg(ADDR, Register::OutputPort, 0b0000_0001),
+        write_reg(ADDR, Register::OutputPort, 0b0000_0101),
+        read_reg(ADDR, Register::OutputPort, 0b0000_0101),
+        write_reg(ADDR, Register::OutputPort, 0b0000_0100),
+        read_reg(ADDR, Register::Config, 0xFF),
+        write_reg(ADDR, Register::Config, 0b1111_1011),
+        read_reg(ADDR, Register::Polarity, 0x00),
+        write_reg(ADDR, Register::Polarity, 0b1000_0000),
+    ]);
+    tca.set_pin_output(2, PinLevel::High).unwrap();
+    tca.toggle_pin_output(0).unwrap();
+    tca.set_pin_config(2, PinConfig::Output).unwrap();
+    tca.set_pin_polarity(7, PinPolarity::Inverted).unwrap();
+    i2c.done();
+}
+
+#[test]
+fn modify_register_rejects_input_port() {
+    let (mut tca, mut i2c) = driver(&[]);
+    assert!(matches!(
+        tca.modify_register(Register::InputPort, |value| value),
+        Err(Tca9534Error::Core(Tca9534CoreError::InvalidArgument))
+    ));
+    i2c.done();
+}
+
+#[test]
+fn embedded_hal_transport_error_is_the_public_error_type() {
+    let (mut tca, mut i2c) =
+        driver(&[read_reg(ADDR, Register::InputPort, 0x00).with_error(ErrorKind::Bus)]);
+    // The annotation fails to compile if the transport error type drifts
+    let result: Result<u8, Tca9534Error<ErrorKind>> = tca.read_input_port();
+    assert!(matches!(result, Err(Tca9534Error::I2c(ErrorKind::Bus))));
+    i2c.done();
+}
+
+#[test]
+fn driver_errors_are_embedded_hal_digital_errors() {
+    let core: Tca9534Error<ErrorKind> = Tca9534CoreError::InvalidPin.into();
+    let bus: Tca9534Error<ErrorKind> = Tca9534Error::I2c(ErrorKind::Bus);
+    for error in [core, bus] {
+        assert_eq!(
+            digital_error_kind(&error),
+            embedded_hal::digital::ErrorKind::Other
+        );
+    }
+}
+
+#[test]
+fn general_call_reset_sends_reset_byte_to_address_zero() {
+    let mut i2c = Mock::new(&[Transaction::write(0x00, vec![0x06])]);
+    Tca9534Sync::general_call_reset(&mut i2c).unwrap();
+    i2c.done();
+}
+
+fn input_reads(values: &[u8]) -> Vec<Transaction> {
+    values
+        .iter()
+        .map(|&value| read_reg(ADDR, Register::InputPort, value))
+        .collect()
+}
+
+#[test]
+fn read_input_port_stable_returns_immediately_stable_value() {
+    let (mut tca, mut i2c) = driver(&input_reads(&[0x5A, 0x5A, 0x5A]));
+    let mut delay = CheckedDelay::new(&[Delay::delay_us(20), Delay::delay_us(20)]);
+    assert_eq!(
+        tca.read_input_port_stable(3, 8, 20, &mut delay).unwrap(),
+        0x5A
+    );
+    delay.done();
+    i2c.done();
+}
+
+#[test]
+fn read_input_port_stable_waits_for_value_to_settle() {
+    let (mut tca, mut i2c) = driver(&input_reads(&[0x01, 0x03, 0x07, 0x07, 0x07]));
+    assert_eq!(
+        tca.read_input_port_stable(3, 8, 20, &mut NoopDelay)
+            .unwrap(),
+        0x07
+    );
+    i2c.done();
+}
+
+#[test]
+fn read_input_port_stable_reports_exhausted_budget() {
+    let (mut tca, mut i2c) = driver(&input_reads(&[0x01, 0x02, 0x01, 0x02]));
+    assert!(matches!(
+        tca.read_input_port_stable(2, 4, 20, &mut NoopDelay),
+        Err(StableReadError::NotStable { last: 0x02 })
+    ));
+    assert!(matches!(
+        tca.read_input_port_stable(5, 4, 20, &mut NoopDelay),
+        Err(StableReadError::Bus(Tca9534Error::Core(
+            Tca9534CoreError::InvalidArgument
+        )))
+    ));
+    i2c.done();
+}
+
+#[test]
+fn polarity_verification_detects_ignored_write() {
+    let (mut tca, mut i2c) = driver(&[
+        // set_port_polarity never reads back
+        write_reg(ADDR, Register::Polarity, 0xF0),
+        read_reg(ADDR, Register::Polarity, 0xF0),
+        write_reg(ADDR, Register::Polarity, 0xF8),
+        read_reg(ADDR, Register::Polarity, 0xF0),
+    ]);
+    tca.set_polarity_verification(true);
+    tca.set_port_polarity(0xF0).unwrap();
+    assert!(matches!(
+        tca.set_pin_polarity(3, PinPolarity::Inverted),
+        Err(Tca9534Error::Core(Tca9534CoreError::PolarityMismatch))
+    ));
+    i2c.done();
+}
+
+#[test]
+fn set_port_polarity_verified_reads_back_without_verification_mode() {
+    let (mut tca, mut i2c) = driver(&[
+        write_reg(ADDR, Register::Polarity, 0x0F),
+        read_reg(ADDR, Register::Polarity, 0x0F),
+        write_reg(ADDR, Register::Polarity, 0xFF),
+        read_reg(ADDR, Register::Polarity, 0x0F),
+    ]);
+    tca.set_port_polarity_verified(0x0F).unwrap();
+    assert!(matches!(
+        tca.set_port_polarity_verified(0xFF),
+        Err(Tca9534Error::Core(Tca9534CoreError::PolarityMismatch))
+    ));
+    i2c.done();
+}
+
+#[test]
+fn format_state_into_writes_fixed_layout() {
+    let (mut tca, mut i2c) = driver(&snapshot_reads(0x1E, 0x0F, 0x30, 0xF0));
+    let mut buf = [0u8; 32];
+    let len = tca.format_state_into(&mut buf).unwrap();
+    assert_eq!(&buf[..len], b"@20 I1E O0F P30 CF0\n");
+
+    let mut short = [0u8; 19];
+    assert!(matches!(
+        tca.format_state_into(&mut short),
+        Err(Tca9534Error::Core(Tca9534CoreError::InvalidArgument))
+    ));
+    i2c.done();
+}